@@ -1,139 +1,1655 @@
-use crate::request::{Endpoint, Request};
-use crate::util::StatefulList;
+use crate::app::bookmarks::Bookmarks;
+use crate::app::command::{Command, CommandLine};
+use crate::app::export::export;
+use crate::app::find::Find;
+use crate::app::history::Visit;
+use crate::app::input::Input;
+use crate::app::mouse::Panels;
+use crate::app::picker::{Picker, PickerKind};
+use crate::app::queries::{QueryHistory, ReverseSearch};
+use crate::app::tab::Tab;
+use crate::config::{validate_lang, Action, Config};
+use crate::request::{Endpoint, Error, Request, SearchResult, Worker};
+use crate::ui::{Rendered, Theme};
+use crate::util::{Debounce, StatefulList};
+use crate::wikitext;
+use crossterm::event::{MouseButton, MouseEvent};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use unicode_width::UnicodeWidthStr;
 
+/// What keys act on: the focused panel, or the command line over it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Mode {
     Search,
+    /// The Page list
     Browse,
     Read,
+    /// Picking a section from the Table of Content
+    Toc,
+    Url,
+    /// Typing a `:` command on the status row
+    Command,
 }
 
-pub struct App<'a> {
-    pub title: &'a str,
-    pub mode: Mode,
-    pub search_input: String,
-    pub search_input_last: String,
-    pub search_cursor_x_max: u16,
+/// The focused panel, kept while the command line is open
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    Search,
+    /// The Page list
+    Browse,
+    Read,
+    /// The Table of Content
+    Toc,
+    Url,
+}
+
+impl Focus {
+    /// The panel after this one in Tab order
+    fn next(self) -> Self {
+        match self {
+            Focus::Search => Focus::Browse,
+            Focus::Browse => Focus::Toc,
+            Focus::Toc => Focus::Read,
+            Focus::Read => Focus::Url,
+            Focus::Url => Focus::Search,
+        }
+    }
+
+    fn previous(self) -> Self {
+        match self {
+            Focus::Search => Focus::Url,
+            Focus::Browse => Focus::Search,
+            Focus::Toc => Focus::Browse,
+            Focus::Read => Focus::Toc,
+            Focus::Url => Focus::Read,
+        }
+    }
+}
+
+impl From<Focus> for Mode {
+    fn from(focus: Focus) -> Self {
+        match focus {
+            Focus::Search => Mode::Search,
+            Focus::Browse => Mode::Browse,
+            Focus::Read => Mode::Read,
+            Focus::Toc => Mode::Toc,
+            Focus::Url => Mode::Url,
+        }
+    }
+}
+
+/// What an in-flight request was made for
+pub enum Loading {
+    Search {
+        search: String,
+        full_text: bool,
+    },
+    /// The next batch of results of the current search
+    More {
+        search: String,
+        offset: u16,
+        full_text: bool,
+    },
+    Page {
+        title: String,
+        lang: String,
+        /// Id of the tab the article is for
+        tab: u64,
+        /// The tab was opened for this article, and goes if it never comes
+        new_tab: bool,
+    },
+    /// A random article, opened once its title is known
+    Random,
+}
+
+pub struct App {
+    pub focus: Focus,
+    pub search_input: Input,
     pub search_cursor_x: u16,
     pub search_cursor_y: u16,
-    pub pages: StatefulList<String>,
-    pub toc: Vec<String>,
-    pub page: Vec<String>,
-    pub page_scroll: u16,
-    pub url: String,
+    pub pages: StatefulList<SearchResult>,
+    /// Search the Page list holds results for
+    pub pages_query: String,
+    /// Submitted searches, recalled with Up and Down
+    pub queries: QueryHistory,
+    /// Ctrl-R search through `queries`, shown in the search box
+    pub reverse_search: Option<ReverseSearch>,
+    pub full_text: bool,
+    /// Offset of the next batch of results, if there are more to load
+    pub pages_next: Option<u16>,
+    /// Total number of results, when the API reports it
+    pub pages_total: Option<u64>,
+    /// Whether the Page list holds full-text results, the endpoint more
+    /// results are loaded from
+    pages_full_text: bool,
+    /// Open articles, there is always at least one
+    pub tabs: Vec<Tab>,
+    /// Index of the tab shown in the Read pane
+    pub tab: usize,
+    /// Id of the last tab opened
+    last_tab_id: u64,
+    /// Rows and columns of the Read pane, set when drawing
+    pub page_height: u16,
+    pub page_width: u16,
+    /// Panel areas, set when drawing
+    pub panels: Panels,
+    /// First character of the URL shown in the URL panel
+    pub url_scroll: usize,
+    pub theme: Theme,
+    pub error: Option<Error>,
+    pub status: String,
+    pub loading: Option<(u64, Loading)>,
+    pub spinner: usize,
+    pub lang: String,
+    pub picker: Option<Picker>,
+    /// In-article find, shown on the status line
+    pub find: Option<Find>,
+    /// The `:` command line, while in command mode
+    pub command: Option<CommandLine>,
+    pub command_cursor_x: u16,
+    pub command_cursor_y: u16,
+    /// Commands run, recalled with Up and Down
+    commands: QueryHistory,
+    bookmarks: Bookmarks,
+    /// Where `:export` writes articles
+    export_dir: Option<PathBuf>,
+    /// Set by `:quit`
+    pub quit: bool,
+    /// Prefix suggestions for the search input, sent once typing pauses
+    suggest: Debounce,
+    /// In-flight suggestions and the input they were asked for
+    suggesting: Option<(u64, String)>,
+    worker: Worker,
 }
 
-const URL: &str = "https://en.wikipedia.org";
+/// Wikipedia editions offered by the language picker, by language code
+const LANGUAGES: [(&str, &str); 20] = [
+    ("en", "English"),
+    ("de", "Deutsch"),
+    ("fr", "Français"),
+    ("ja", "日本語"),
+    ("es", "Español"),
+    ("ru", "Русский"),
+    ("it", "Italiano"),
+    ("zh", "中文"),
+    ("pt", "Português"),
+    ("pl", "Polski"),
+    ("nl", "Nederlands"),
+    ("sv", "Svenska"),
+    ("uk", "Українська"),
+    ("ar", "العربية"),
+    ("fa", "فارسی"),
+    ("ko", "한국어"),
+    ("he", "עברית"),
+    ("tr", "Türkçe"),
+    ("vi", "Tiếng Việt"),
+    ("simple", "Simple English"),
+];
+
+/// Entries skipped by PageUp and PageDown in lists
+const PAGE_JUMP: usize = 10;
+
+/// How close to the end of the page list the next results are loaded
+const LOAD_MORE_MARGIN: usize = 5;
+
+/// Rows the Read pane moves by for each step of the mouse wheel
+const WHEEL_ROWS: usize = 3;
 
-impl<'a> App<'a> {
-    pub fn new(title: &'a str) -> Self {
+/// Pause in typing after which suggestions are fetched
+const SUGGEST_DELAY: Duration = Duration::from_millis(250);
+
+fn base_url(lang: &str) -> String {
+    format!("https://{}.wikipedia.org", lang)
+}
+
+impl App {
+    /// The query and command history and the bookmarks are kept in
+    /// `data_dir`, or only in memory without one.
+    pub fn new(config: Config, worker: Worker, data_dir: Option<PathBuf>) -> Self {
         Self {
-            title,
-            mode: Mode::Search,
-            search_input: String::default(),
-            search_input_last: String::default(),
-            search_cursor_x_max: 0,
+            focus: Focus::Search,
+            search_input: Input::default(),
             search_cursor_x: 0,
             search_cursor_y: 0,
             pages: StatefulList::default(),
-            toc: Vec::new(),
-            page: Vec::new(),
-            page_scroll: 0,
-            url: URL.to_owned(),
+            pages_query: String::default(),
+            queries: QueryHistory::load(data_dir.as_ref().map(|dir| dir.join("queries"))),
+            reverse_search: None,
+            full_text: false,
+            pages_next: None,
+            pages_total: None,
+            pages_full_text: false,
+            tabs: vec![Tab::new(0, &config.lang, base_url(&config.lang))],
+            export_dir: config.export_dir(),
+            tab: 0,
+            last_tab_id: 0,
+            page_height: 0,
+            page_width: 0,
+            panels: Panels::default(),
+            url_scroll: 0,
+            theme: Theme::default(),
+            error: None,
+            status: String::default(),
+            loading: None,
+            spinner: 0,
+            lang: config.lang,
+            picker: None,
+            find: None,
+            command: None,
+            command_cursor_x: 0,
+            command_cursor_y: 0,
+            commands: QueryHistory::load(data_dir.as_ref().map(|dir| dir.join("commands"))),
+            bookmarks: Bookmarks::load(data_dir.map(|dir| dir.join("bookmarks"))),
+            quit: false,
+            suggest: Debounce::new(SUGGEST_DELAY),
+            suggesting: None,
+            worker,
+        }
+    }
+
+    pub fn current_tab(&self) -> &Tab {
+        &self.tabs[self.tab]
+    }
+
+    fn current_tab_mut(&mut self) -> &mut Tab {
+        &mut self.tabs[self.tab]
+    }
+
+    /// What keys act on: the command line while it is open, else the
+    /// focused panel
+    pub fn mode(&self) -> Mode {
+        match self.command {
+            Some(_) => Mode::Command,
+            None => self.focus.into(),
         }
     }
 
+    /// Whether characters typed go to a text field rather than the keymap
+    pub fn is_typing(&self) -> bool {
+        let finding = self.find.as_ref().is_some_and(|find| find.editing);
+        let text_mode = matches!(self.mode(), Mode::Search | Mode::Command);
+        self.picker.is_none() && (text_mode || finding)
+    }
+
+    /// Type `key` in the focused text field.
     pub fn on_key(&mut self, key: char) {
-        if let Mode::Search = self.mode {
-            if self.search_input.len() < self.search_cursor_x_max as usize {
-                self.search_input.push(key);
+        if self.picker.is_some() || self.error.is_some() {
+            return;
+        }
+        if let Some(find) = self.find.as_mut().filter(|find| find.editing) {
+            find.query.push(key);
+            self.update_find(true);
+            return;
+        }
+        if let Some(search) = self.reverse_search.as_mut() {
+            search.query.push(key);
+            search.found = self.queries.find(&search.query, usize::MAX);
+            return;
+        }
+        self.edit_input(|input| input.insert(key));
+    }
+
+    /// Run an action of the keymap. Quitting is up to the caller.
+    pub fn on_action(&mut self, action: Action) {
+        // The error popup takes every key until it is dismissed
+        if self.error.is_some() && !matches!(action, Action::Escape | Action::Enter) {
+            return;
+        }
+        match action {
+            Action::Quit => {}
+            Action::Escape => self.on_escape(),
+            Action::Enter => self.on_enter(),
+            Action::Up => self.on_up(),
+            Action::Down => self.on_down(),
+            Action::Left => self.on_left(),
+            Action::Right => self.on_right(),
+            Action::PageUp => self.on_page_up(),
+            Action::PageDown => self.on_page_down(),
+            Action::Home => self.on_home(),
+            Action::End => self.on_end(),
+            Action::Backspace => self.on_backspace(),
+            Action::Delete => self.on_delete(),
+            Action::WordLeft => self.on_word_left(),
+            Action::WordRight => self.on_word_right(),
+            // Anything else waits until the picker is closed
+            _ if self.picker.is_some() => {}
+            Action::FocusNext => self.on_tab(),
+            Action::FocusPrevious => self.on_backtab(),
+            Action::LineStart => self.edit_input(Input::home),
+            Action::LineEnd => self.edit_input(Input::end),
+            Action::DeleteWord => self.edit_input(Input::delete_word),
+            Action::DeleteToStart => self.edit_input(Input::delete_to_start),
+            // Anything else waits until the command line is closed
+            _ if self.command.is_some() => {}
+            Action::Command => {
+                self.command = Some(CommandLine::default());
             }
+            Action::HalfPageUp if self.mode() == Mode::Read => self.scroll_up(self.half_page()),
+            Action::HalfPageDown if self.mode() == Mode::Read => self.scroll_down(self.half_page()),
+            Action::FocusSearch => self.set_focus(Focus::Search),
+            Action::ReverseSearch if self.mode() == Mode::Search => self.search_queries(),
+            Action::FullText if self.mode() == Mode::Search => {
+                self.full_text = !self.full_text;
+                self.pages_query.clear();
+                self.cancel_suggestions();
+            }
+            Action::Language => self.open_language_picker(),
+            Action::LanguageLinks => self.open_langlink_picker(),
+            Action::Contents => self.enter_toc(),
+            Action::PreviousLink if self.mode() == Mode::Read => self.select_link(false),
+            Action::NextLink if self.mode() == Mode::Read => self.select_link(true),
+            Action::OpenInNewTab if self.mode() == Mode::Read => self.open_link(true),
+            Action::Find if self.mode() == Mode::Read => self.find = Some(Find::new()),
+            Action::FindNext => self.find_next(true),
+            Action::FindPrevious => self.find_next(false),
+            Action::FindCase | Action::FindRegex => {
+                if let Some(find) = &mut self.find {
+                    if action == Action::FindCase {
+                        find.case_sensitive = !find.case_sensitive;
+                    } else {
+                        find.regex = !find.regex;
+                    }
+                    self.update_find(true);
+                }
+            }
+            Action::Back => self.go_back(),
+            Action::Forward => self.go_forward(),
+            Action::NewTab => self.new_tab(),
+            Action::CloseTab => self.close_tab(),
+            Action::PreviousTab => {
+                self.switch_tab((self.tab + self.tabs.len() - 1) % self.tabs.len())
+            }
+            Action::NextTab => self.switch_tab((self.tab + 1) % self.tabs.len()),
+            Action::Tab(n) => self.switch_tab(n - 1),
+            _ => {}
         }
     }
 
     pub fn on_backspace(&mut self) {
-        if let Mode::Search = self.mode {
-            self.search_input.pop();
+        if self.picker.is_some() {
+            return;
+        }
+        if let Some(find) = self.find.as_mut().filter(|find| find.editing) {
+            find.query.pop();
+            self.update_find(true);
+            return;
+        }
+        if let Some(search) = self.reverse_search.as_mut() {
+            search.query.pop();
+            search.found = self.queries.find(&search.query, usize::MAX);
+            return;
+        }
+        match self.mode() {
+            Mode::Search => self.edit_input(Input::backspace),
+            Mode::Command if self.command.as_ref().is_some_and(|c| c.input.is_empty()) => {
+                self.close_command()
+            }
+            Mode::Command => self.edit_input(Input::backspace),
+            Mode::Browse | Mode::Toc | Mode::Url => {}
+            Mode::Read => self.go_back(),
+        }
+    }
+
+    pub fn on_delete(&mut self) {
+        if self.picker.is_none() {
+            self.edit_input(Input::delete);
+        }
+    }
+
+    pub fn on_word_left(&mut self) {
+        if self.picker.is_none() {
+            self.edit_input(Input::word_left);
+        }
+    }
+
+    pub fn on_word_right(&mut self) {
+        if self.picker.is_none() {
+            self.edit_input(Input::word_right);
+        }
+    }
+
+    /// Apply `edit` to the text field of the focused mode, if it has one.
+    fn edit_input(&mut self, edit: impl FnOnce(&mut Input)) {
+        match self.mode() {
+            Mode::Search => {
+                self.accept_reverse_search();
+                self.edit_search(edit);
+            }
+            Mode::Command => {
+                if let Some(command) = &mut self.command {
+                    edit(&mut command.input);
+                    command.error = None;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Start a Ctrl-R search, or look further back for the same query.
+    fn search_queries(&mut self) {
+        match self.reverse_search.as_mut() {
+            Some(search) => {
+                let before = search.found.unwrap_or(usize::MAX);
+                if let Some(i) = self.queries.find(&search.query, before) {
+                    search.found = Some(i);
+                }
+            }
+            None => self.reverse_search = Some(ReverseSearch::default()),
+        }
+    }
+
+    /// End a Ctrl-R search, putting the query found in the search input.
+    fn accept_reverse_search(&mut self) {
+        let search = match self.reverse_search.take() {
+            Some(search) => search,
+            None => return,
+        };
+        if let Some(query) = search.found.and_then(|i| self.queries.get(i)) {
+            let query = query.to_owned();
+            self.edit_search(|input| input.set(&query));
+        }
+    }
+
+    fn recall_query(&mut self, older: bool) {
+        self.accept_reverse_search();
+        let input = self.search_input.as_str();
+        let query = if older {
+            self.queries.older(input)
+        } else {
+            self.queries.newer(input)
+        };
+        if let Some(query) = query.map(str::to_owned) {
+            self.edit_search(|input| input.set(&query));
+        }
+    }
+
+    /// Apply `edit` to the search input, asking for new suggestions if it
+    /// changed the text.
+    fn edit_search(&mut self, edit: impl FnOnce(&mut Input)) {
+        let before = self.search_input.as_str().to_owned();
+        edit(&mut self.search_input);
+        if self.search_input.as_str() != before {
+            self.suggest.poke(Instant::now());
         }
     }
 
     pub fn on_enter(&mut self) {
-        match self.mode {
+        if self.error.is_some() {
+            self.error = None;
+            return;
+        }
+        if let Some(picker) = self.picker.take() {
+            self.on_pick(picker);
+            return;
+        }
+        if let Some(find) = self.find.as_mut().filter(|find| find.editing) {
+            find.editing = false;
+            if find.query.is_empty() {
+                self.find = None;
+            }
+            return;
+        }
+        match self.mode() {
+            Mode::Command => self.run_command(),
             Mode::Search => {
-                if self.search_input == "" {
+                self.accept_reverse_search();
+                if self.search_input.is_empty() {
                     return;
                 }
-                if self.search_input == self.search_input_last {
-                    self.mode = Mode::Browse;
+                self.cancel_suggestions();
+                if let Err(err) = self.queries.push(self.search_input.as_str()) {
+                    self.status = format!("Could not save the search history: {}", err);
+                }
+                if self.search_input.as_str() == self.pages_query {
+                    self.focus = Focus::Browse;
                     return;
                 }
-                let ps = Request::new(&self.search_input, Endpoint::PrefixSearch).fetch();
-                self.mode = Mode::Browse;
-                self.search_input_last = self.search_input.clone();
-                self.pages = StatefulList::with_items(ps.page_list);
+                self.request(Loading::Search {
+                    search: self.search_input.as_str().to_owned(),
+                    full_text: self.full_text,
+                });
             }
             Mode::Browse => {
                 if let Some(i) = self.pages.state.selected() {
-                    let page = &self.pages.items[i];
-                    let ps = Request::new(page, Endpoint::PageParse).fetch();
-                    self.toc = ps.toc_list;
-                    self.mode = Mode::Read;
-                    self.page = ps.page;
-                    self.url = format!("{}/wiki/{}", URL, page.as_str().replace(" ", "_"));
+                    self.load_page(self.pages.items[i].title.clone(), self.lang.clone());
+                }
+            }
+            Mode::Read => self.open_link(false),
+            Mode::Toc => {
+                let rendered = self.rendered();
+                let tab = self.current_tab();
+                let selected = tab.toc.state.selected();
+                if let Some(row) = selected.and_then(|i| tab.section_row(&rendered, i)) {
+                    self.scroll_to(row);
                 }
+                self.focus = Focus::Read;
+            }
+            Mode::Url => {}
+        }
+    }
+
+    /// Open the selected link, in a new tab if `new_tab` is set.
+    fn open_link(&mut self, new_tab: bool) {
+        let links = self.rendered();
+        let tab = self.current_tab();
+        let target = match tab.link.and_then(|i| links.links.get(i)) {
+            Some((target, _)) => target.clone(),
+            None => return,
+        };
+        let lang = tab.lang.clone();
+        if let Some(section) = target.strip_prefix('#') {
+            // A section of this article
+            if let Some(row) = self.section_row(section) {
+                self.scroll_to(row);
+            }
+            return;
+        }
+        let title = target.trim_start_matches(':').to_owned();
+        if new_tab {
+            // Stay in the Read pane, where the article will show up
+            let tab = self.blank_tab();
+            self.tabs.push(tab);
+            self.tab = self.tabs.len() - 1;
+        }
+        self.request(Loading::Page {
+            title,
+            lang,
+            tab: self.current_tab().id,
+            new_tab,
+        });
+    }
+
+    /// Load an article into the current tab.
+    fn load_page(&mut self, title: String, lang: String) {
+        self.request(Loading::Page {
+            title,
+            lang,
+            tab: self.current_tab().id,
+            new_tab: false,
+        });
+    }
+
+    fn blank_tab(&mut self) -> Tab {
+        self.last_tab_id += 1;
+        Tab::new(self.last_tab_id, &self.lang, base_url(&self.lang))
+    }
+
+    /// Open an empty tab and go search for an article to show in it.
+    fn new_tab(&mut self) {
+        let tab = self.blank_tab();
+        self.tabs.push(tab);
+        self.tab = self.tabs.len() - 1;
+        self.focus = Focus::Search;
+    }
+
+    fn close_tab(&mut self) {
+        if self.tabs.len() == 1 {
+            self.tabs[0] = self.blank_tab();
+            self.focus = Focus::Browse;
+            return;
+        }
+        self.remove_tab(self.tab);
+    }
+
+    /// Remove the `i`th tab, keeping the current one shown if it is another.
+    fn remove_tab(&mut self, i: usize) {
+        self.tabs.remove(i);
+        if i < self.tab {
+            self.tab -= 1;
+        }
+        self.tab = self.tab.min(self.tabs.len() - 1);
+    }
+
+    /// Cancel the request in flight. A tab opened for an article that is not
+    /// coming goes with it.
+    fn cancel_loading(&mut self) {
+        if let Some((id, loading)) = self.loading.take() {
+            self.worker.cancel(id);
+            self.drop_new_tab(&loading);
+        }
+    }
+
+    fn drop_new_tab(&mut self, loading: &Loading) {
+        if let Loading::Page {
+            tab, new_tab: true, ..
+        } = loading
+        {
+            let i = self.tabs.iter().position(|t| t.id == *tab);
+            if let Some(i) = i.filter(|&i| self.tabs[i].is_empty() && self.tabs.len() > 1) {
+                self.remove_tab(i);
             }
-            Mode::Read => {}
         }
     }
 
+    fn switch_tab(&mut self, i: usize) {
+        if i < self.tabs.len() {
+            self.tab = i;
+        }
+    }
+
+    pub fn on_tab(&mut self) {
+        if self.picker.is_some() || self.error.is_some() {
+            return;
+        }
+        if self.command.is_some() {
+            return self.complete_command(true);
+        }
+        self.set_focus(self.focus.next());
+    }
+
+    pub fn on_backtab(&mut self) {
+        if self.picker.is_some() || self.error.is_some() {
+            return;
+        }
+        if self.command.is_some() {
+            return self.complete_command(false);
+        }
+        self.set_focus(self.focus.previous());
+    }
+
     pub fn on_escape(&mut self) {
-        match self.mode {
-            Mode::Search => {}
-            Mode::Browse => self.mode = Mode::Search,
-            Mode::Read => self.mode = Mode::Browse,
+        if self.error.is_some() {
+            self.error = None;
+            return;
+        }
+        if self.picker.take().is_some() {
+            return;
+        }
+        if self.reverse_search.take().is_some() {
+            return;
+        }
+        if self.command.is_some() {
+            return self.close_command();
+        }
+        // A find only outlives its prompt in the Read pane
+        let editing = self.find.as_ref().is_some_and(|find| find.editing);
+        if (editing || self.mode() == Mode::Read) && self.find.take().is_some() {
+            return;
+        }
+        if self.loading.is_some() {
+            self.cancel_loading();
+            return;
+        }
+        self.focus = match self.focus {
+            Focus::Search | Focus::Browse => Focus::Search,
+            Focus::Read => Focus::Browse,
+            Focus::Toc | Focus::Url => Focus::Read,
+        };
+    }
+
+    pub fn on_tick(&mut self) {
+        if self.loading.is_some() {
+            self.spinner = self.spinner.wrapping_add(1);
+        }
+        if self.suggest.ready(Instant::now()) {
+            self.suggest_pages();
+        }
+    }
+
+    /// Fill the Page list with titles starting with the search input,
+    /// without leaving Search mode.
+    fn suggest_pages(&mut self) {
+        if let Some((id, _)) = self.suggesting.take() {
+            self.worker.cancel(id);
+        }
+        if self.mode() != Mode::Search
+            || self.full_text
+            || self.search_input.is_empty()
+            || self.search_input.as_str() == self.pages_query
+        {
+            return;
+        }
+        let search = self.search_input.as_str().to_owned();
+        let req = Request::new(&search, &self.lang, Endpoint::PrefixSearch);
+        self.suggesting = Some((self.worker.fetch(req), search));
+    }
+
+    fn cancel_suggestions(&mut self) {
+        self.suggest.cancel();
+        if let Some((id, _)) = self.suggesting.take() {
+            self.worker.cancel(id);
+        }
+    }
+
+    /// Show suggestions unless the input changed since they were asked for,
+    /// the Page list was left, or a search for the same input is on its way.
+    fn on_suggestions(&mut self, search: String, res: Result<Box<Request>, Error>) {
+        let searching = matches!(
+            &self.loading,
+            Some((_, Loading::Search { search: current, .. })) if *current == search
+        );
+        if search != self.search_input.as_str()
+            || self.full_text
+            || self.mode() != Mode::Search
+            || searching
+        {
+            return;
+        }
+        if let Ok(req) = res {
+            // More results of the list being replaced would land under these
+            if let Some((id, Loading::More { .. })) = &self.loading {
+                self.worker.cancel(*id);
+                self.loading = None;
+            }
+            self.pages_query = search;
+            self.pages = StatefulList::with_items(req.page_list);
+            self.pages_next = req.next_offset;
+            self.pages_total = req.total;
+            self.pages_full_text = false;
+        }
+    }
+
+    /// Start a request in the background, replacing the one in flight.
+    fn request(&mut self, loading: Loading) {
+        self.cancel_loading();
+        let endpoint = |full_text| {
+            if full_text {
+                Endpoint::FullTextSearch
+            } else {
+                Endpoint::PrefixSearch
+            }
+        };
+        let req = match &loading {
+            Loading::Search { search, full_text } => {
+                Request::new(search, &self.lang, endpoint(*full_text))
+            }
+            Loading::More {
+                search,
+                offset,
+                full_text,
+            } => Request::with_offset(search, &self.lang, *offset, endpoint(*full_text)),
+            Loading::Page { title, lang, .. } => {
+                let title = title.split('#').next().unwrap_or_default();
+                Request::new(title, lang, Endpoint::PageParse)
+            }
+            Loading::Random => Request::new("", &self.lang, Endpoint::Random),
+        };
+        let id = self.worker.fetch(req);
+        self.loading = Some((id, loading));
+    }
+
+    /// Handle a response from the worker. Errors go to the error popup and
+    /// warnings to the status line, responses to cancelled requests are
+    /// dropped.
+    pub fn on_response(&mut self, id: u64, res: Result<Box<Request>, Error>) {
+        self.worker.done(id);
+        if let Some((current, _)) = &self.suggesting {
+            if *current == id {
+                let (_, search) = self.suggesting.take().unwrap();
+                self.on_suggestions(search, res);
+                return;
+            }
+        }
+        let loading = match self.loading.take() {
+            Some((current, loading)) if current == id => loading,
+            other => {
+                self.loading = other;
+                return;
+            }
+        };
+        let req = match res {
+            Ok(req) => req,
+            Err(err) => {
+                self.drop_new_tab(&loading);
+                self.error = Some(err);
+                return;
+            }
+        };
+        self.status = req.warnings.join(" ");
+        match loading {
+            Loading::Search { search, full_text } => {
+                self.focus = Focus::Browse;
+                self.pages_query = search;
+                self.pages = StatefulList::with_items(req.page_list);
+                self.pages_next = req.next_offset;
+                self.pages_total = req.total;
+                self.pages_full_text = full_text;
+            }
+            Loading::More {
+                search, full_text, ..
+            } => {
+                if search != self.pages_query || full_text != self.pages_full_text {
+                    return;
+                }
+                self.pages.items.extend(req.page_list);
+                self.pages_next = req.next_offset;
+                self.pages_total = req.total.or(self.pages_total);
+            }
+            Loading::Random => {
+                if let Some(page) = req.page_list.into_iter().next() {
+                    self.load_page(page.title, req.lang);
+                }
+            }
+            Loading::Page { title, tab, .. } => {
+                // The tab may have been closed meanwhile
+                let i = match self.tabs.iter().position(|t| t.id == tab) {
+                    Some(i) => i,
+                    None => return,
+                };
+                let fragment = req.fragment.or_else(|| {
+                    title
+                        .split_once('#')
+                        .map(|(_, fragment)| fragment.to_owned())
+                });
+                let title = if req.title.is_empty() {
+                    title
+                } else {
+                    req.title
+                };
+                let mut url = format!("{}/wiki/{}", base_url(&req.lang), title.replace(" ", "_"));
+                if let Some(fragment) = &fragment {
+                    url = format!("{}#{}", url, fragment.replace(" ", "_"));
+                }
+                if i == self.tab {
+                    if !req.disambiguation.is_empty() {
+                        self.open_disambiguation_picker(&title, &req.lang, req.disambiguation);
+                    }
+                    self.focus = Focus::Read;
+                } else {
+                    self.status = format!("Opened \"{}\" in tab {}", title, i + 1);
+                }
+                let width = self.page_width as usize;
+                let tab = &mut self.tabs[i];
+                tab.open(Visit {
+                    title,
+                    page: req.page,
+                    toc: req.toc_list,
+                    langlinks: req.langlinks,
+                    lang: req.lang,
+                    url,
+                    scroll: 0,
+                });
+                let rendered = tab.rendered(width, &self.theme);
+                if let Some(row) = fragment.and_then(|fragment| rendered.heading_line(&fragment)) {
+                    // Scrolling past the end is undone when drawing
+                    tab.scroll = row;
+                }
+            }
         }
     }
 
     pub fn on_up(&mut self) {
-        match self.mode {
-            Mode::Search => {}
+        if let Some(picker) = &mut self.picker {
+            picker.list.previous(1);
+            return;
+        }
+        match self.mode() {
+            Mode::Search => self.recall_query(true),
+            Mode::Command => self.recall_command(true),
             Mode::Browse => self.pages.previous(1),
             Mode::Read => self.scroll_up(1),
+            Mode::Toc => self.current_tab_mut().toc.previous(1),
+            Mode::Url => {}
         }
     }
 
     pub fn on_down(&mut self) {
-        match self.mode {
-            Mode::Search => {}
-            Mode::Browse => self.pages.next(1),
+        if let Some(picker) = &mut self.picker {
+            picker.list.next(1);
+            return;
+        }
+        match self.mode() {
+            Mode::Search => self.recall_query(false),
+            Mode::Command => self.recall_command(false),
+            Mode::Browse => {
+                self.pages.next(1);
+                self.load_more();
+            }
             Mode::Read => self.scroll_down(1),
+            Mode::Toc => self.current_tab_mut().toc.next(1),
+            Mode::Url => {}
         }
     }
 
     pub fn on_left(&mut self) {
-        match self.mode {
-            Mode::Search => {}
+        if let Some(picker) = &mut self.picker {
+            picker.list.previous(5);
+            return;
+        }
+        match self.mode() {
+            Mode::Search | Mode::Command => self.edit_input(Input::left),
             Mode::Browse => self.pages.previous(5),
-            Mode::Read => self.scroll_up(5),
+            Mode::Read => self.scroll_up(self.half_page()),
+            Mode::Toc => self.current_tab_mut().toc.previous(5),
+            Mode::Url => self.url_scroll = self.url_scroll.saturating_sub(1),
         }
     }
 
     pub fn on_right(&mut self) {
-        match self.mode {
-            Mode::Search => {}
-            Mode::Browse => self.pages.next(5),
-            Mode::Read => self.scroll_down(5),
+        if let Some(picker) = &mut self.picker {
+            picker.list.next(5);
+            return;
+        }
+        match self.mode() {
+            Mode::Search | Mode::Command => self.edit_input(Input::right),
+            Mode::Browse => {
+                self.pages.next(5);
+                self.load_more();
+            }
+            Mode::Read => self.scroll_down(self.half_page()),
+            Mode::Toc => self.current_tab_mut().toc.next(5),
+            Mode::Url => {
+                let len = self.current_tab().url.chars().count();
+                self.url_scroll = (self.url_scroll + 1).min(len.saturating_sub(1));
+            }
+        }
+    }
+
+    pub fn on_page_up(&mut self) {
+        if let Some(picker) = &mut self.picker {
+            picker.list.previous(PAGE_JUMP);
+            return;
+        }
+        match self.mode() {
+            Mode::Browse => self.pages.previous(PAGE_JUMP),
+            Mode::Read => self.scroll_up((self.page_height as usize).max(1)),
+            Mode::Toc => self.current_tab_mut().toc.previous(PAGE_JUMP),
+            Mode::Search | Mode::Url | Mode::Command => {}
+        }
+    }
+
+    pub fn on_page_down(&mut self) {
+        if let Some(picker) = &mut self.picker {
+            picker.list.next(PAGE_JUMP);
+            return;
+        }
+        match self.mode() {
+            Mode::Browse => {
+                self.pages.next(PAGE_JUMP);
+                self.load_more();
+            }
+            Mode::Read => self.scroll_down((self.page_height as usize).max(1)),
+            Mode::Toc => self.current_tab_mut().toc.next(PAGE_JUMP),
+            Mode::Search | Mode::Url | Mode::Command => {}
+        }
+    }
+
+    pub fn on_home(&mut self) {
+        if let Some(picker) = &mut self.picker {
+            picker.list.state.select(Some(0));
+            return;
+        }
+        match self.mode() {
+            Mode::Search | Mode::Command => self.edit_input(Input::home),
+            Mode::Browse if !self.pages.items.is_empty() => self.pages.state.select(Some(0)),
+            Mode::Read => self.scroll_to(0),
+            Mode::Url => self.url_scroll = 0,
+            _ => {}
+        }
+    }
+
+    pub fn on_end(&mut self) {
+        if let Some(picker) = &mut self.picker {
+            let last = picker.list.items.len().saturating_sub(1);
+            picker.list.state.select(Some(last));
+            return;
+        }
+        match self.mode() {
+            Mode::Search | Mode::Command => self.edit_input(Input::end),
+            Mode::Browse if !self.pages.items.is_empty() => {
+                self.pages.state.select(Some(self.pages.items.len() - 1));
+                self.load_more();
+            }
+            Mode::Read => self.scroll_to(usize::MAX),
+            Mode::Url => self.url_scroll = usize::MAX,
+            _ => {}
+        }
+    }
+
+    fn half_page(&self) -> usize {
+        (self.page_height as usize / 2).max(1)
+    }
+
+    /// Fetch the next batch of results once the selection nears the end of
+    /// the list.
+    fn load_more(&mut self) {
+        let offset = match self.pages_next {
+            Some(offset) if self.loading.is_none() => offset,
+            _ => return,
+        };
+        let selected = self.pages.state.selected().unwrap_or(0);
+        if selected + LOAD_MORE_MARGIN >= self.pages.items.len() {
+            self.request(Loading::More {
+                search: self.pages_query.clone(),
+                offset,
+                full_text: self.pages_full_text,
+            });
+        }
+    }
+
+    fn open_language_picker(&mut self) {
+        let mut languages = LANGUAGES
+            .iter()
+            .map(|(code, name)| (code.to_string(), format!("{:<7}{}", code, name)))
+            .collect::<Vec<_>>();
+        if !languages.iter().any(|(code, _)| *code == self.lang) {
+            languages.insert(0, (self.lang.clone(), self.lang.clone()));
+        }
+        let current = languages.iter().position(|(code, _)| *code == self.lang);
+        let (codes, labels) = languages.into_iter().unzip();
+        let mut picker = Picker::new("Wikipedia edition", labels, PickerKind::Language(codes));
+        picker.list.state.select(current);
+        self.picker = Some(picker);
+    }
+
+    fn open_langlink_picker(&mut self) {
+        let langlinks = &self.current_tab().langlinks;
+        if langlinks.is_empty() {
+            self.status = "This article is not available in other languages".to_owned();
+            return;
+        }
+        let labels = langlinks
+            .iter()
+            .map(|link| format!("{:<7}{:<16}{}", link.lang, link.autonym, link.title))
+            .collect();
+        let links = langlinks
+            .iter()
+            .map(|link| (link.lang.clone(), link.title.clone()))
+            .collect();
+        self.picker = Some(Picker::new(
+            "Other languages",
+            labels,
+            PickerKind::Article(links),
+        ));
+    }
+
+    fn open_disambiguation_picker(
+        &mut self,
+        title: &str,
+        lang: &str,
+        entries: Vec<(String, String)>,
+    ) {
+        let labels = entries
+            .iter()
+            .map(|(title, description)| {
+                if description.is_empty() {
+                    title.clone()
+                } else {
+                    format!("{} — {}", title, description)
+                }
+            })
+            .collect();
+        let links = entries
+            .into_iter()
+            .map(|(title, _)| (lang.to_owned(), title))
+            .collect();
+        self.picker = Some(Picker::new(
+            &format!("{} may refer to", title),
+            labels,
+            PickerKind::Article(links),
+        ));
+    }
+
+    fn on_pick(&mut self, picker: Picker) {
+        let i = match picker.list.state.selected() {
+            Some(i) => i,
+            None => return,
+        };
+        match picker.kind {
+            PickerKind::Language(mut codes) => self.set_lang(codes.swap_remove(i)),
+            PickerKind::Article(mut links) => {
+                let (lang, title) = links.swap_remove(i);
+                self.load_page(title, lang);
+            }
+        }
+    }
+
+    /// Switch the Wikipedia edition used by every request from now on. The
+    /// open article stays, search results of the previous edition are dropped.
+    fn set_lang(&mut self, lang: String) {
+        if lang == self.lang {
+            return;
+        }
+        self.lang = lang;
+        // Results still on their way are for the previous edition
+        self.cancel_suggestions();
+        if let Some((id, Loading::Search { .. } | Loading::More { .. })) = &self.loading {
+            self.worker.cancel(*id);
+            self.loading = None;
+        }
+        self.pages_query.clear();
+        self.pages = StatefulList::default();
+        self.pages_next = None;
+        self.pages_total = None;
+        if self.focus == Focus::Browse {
+            self.focus = Focus::Search;
+        }
+        let url = base_url(&self.lang);
+        let tab = self.current_tab_mut();
+        if tab.is_empty() {
+            tab.url = url;
+        }
+    }
+
+    fn close_command(&mut self) {
+        self.command = None;
+    }
+
+    fn recall_command(&mut self, older: bool) {
+        if let Some(command) = &mut self.command {
+            let input = command.input.as_str();
+            let line = if older {
+                self.commands.older(input)
+            } else {
+                self.commands.newer(input)
+            };
+            if let Some(line) = line {
+                command.input.set(line);
+            }
+        }
+    }
+
+    fn complete_command(&mut self, forward: bool) {
+        let mut command = match self.command.take() {
+            Some(command) => command,
+            None => return,
+        };
+        command.complete(forward, |name| self.command_arguments(name));
+        self.command = Some(command);
+    }
+
+    /// What the argument of the command `name` can be completed with
+    fn command_arguments(&mut self, name: &str) -> Vec<String> {
+        match name {
+            "open" => {
+                let mut titles = self
+                    .pages
+                    .items
+                    .iter()
+                    .map(|page| page.title.clone())
+                    .collect::<Vec<_>>();
+                titles.extend(self.tabs.iter().map(|tab| tab.title.clone()));
+                titles.extend(
+                    self.bookmarks
+                        .entries
+                        .iter()
+                        .map(|(_, title)| title.clone()),
+                );
+                titles.extend(
+                    self.rendered()
+                        .links
+                        .iter()
+                        .map(|(target, _)| target.clone())
+                        .filter(|target| !target.starts_with('#')),
+                );
+                titles.retain(|title| !title.is_empty());
+                titles
+            }
+            "lang" => LANGUAGES.iter().map(|(code, _)| code.to_string()).collect(),
+            "export" => vec!["md".to_owned()],
+            "set" => Theme::NAMES
+                .iter()
+                .map(|name| format!("theme {}", name))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Run the command typed, keeping the command line open if it fails.
+    fn run_command(&mut self) {
+        let mut command = match self.command.take() {
+            Some(command) => command,
+            None => return,
+        };
+        let line = command.input.as_str().trim().to_owned();
+        if line.is_empty() {
+            return;
+        }
+        if let Err(err) = self.commands.push(&line) {
+            self.status = format!("Could not save the command history: {}", err);
+        }
+        if let Err(err) = Command::parse(&line).and_then(|c| self.execute(c)) {
+            command.error = Some(err);
+            self.command = Some(command);
+        }
+    }
+
+    fn execute(&mut self, command: Command) -> Result<(), String> {
+        match command {
+            Command::Open(title) => self.load_page(title, self.lang.clone()),
+            Command::Lang(lang) => {
+                validate_lang(&lang)?;
+                self.set_lang(lang);
+            }
+            Command::Random => self.request(Loading::Random),
+            Command::Export => {
+                let tab = self.current_tab();
+                if tab.is_empty() {
+                    return Err("No article to export".to_owned());
+                }
+                let dir = self
+                    .export_dir
+                    .as_ref()
+                    .ok_or("No directory to export to, set export_dir in config.toml")?;
+                let markdown = wikitext::to_markdown(&tab.title, &tab.page, &base_url(&tab.lang));
+                let path = export(dir, &tab.title, &markdown)
+                    .map_err(|err| format!("Could not export to {}: {}", dir.display(), err))?;
+                self.status = format!("Exported to {}", path.display());
+            }
+            Command::Bookmark => {
+                let tab = self.current_tab();
+                if tab.is_empty() {
+                    return Err("No article to bookmark".to_owned());
+                }
+                let (lang, title) = (tab.lang.clone(), tab.title.clone());
+                self.status = match self.bookmarks.add(&lang, &title) {
+                    Ok(true) => format!("Bookmarked \"{}\"", title),
+                    Ok(false) => format!("\"{}\" is already bookmarked", title),
+                    Err(err) => return Err(format!("Could not save the bookmark: {}", err)),
+                };
+            }
+            Command::Bookmarks => {
+                if self.bookmarks.entries.is_empty() {
+                    return Err("No bookmarks yet, add one with :bookmark".to_owned());
+                }
+                let labels = self
+                    .bookmarks
+                    .entries
+                    .iter()
+                    .map(|(lang, title)| format!("{:<7}{}", lang, title))
+                    .collect();
+                self.picker = Some(Picker::new(
+                    "Bookmarks",
+                    labels,
+                    PickerKind::Article(self.bookmarks.entries.clone()),
+                ));
+            }
+            Command::SetTheme(name) => {
+                self.theme = Theme::named(&name).ok_or_else(|| {
+                    format!(
+                        "Unknown theme '{}', try {}",
+                        name,
+                        Theme::NAMES.join(" or ")
+                    )
+                })?;
+                self.tabs.iter_mut().for_each(Tab::forget_rendered);
+            }
+            Command::Quit => self.quit = true,
+        }
+        Ok(())
+    }
+
+    /// Move focus to the Table of Content, starting at the section on screen.
+    fn enter_toc(&mut self) {
+        if self.current_tab().toc.items.is_empty() {
+            self.status = "This article has no sections".to_owned();
+            return;
         }
+        self.set_focus(Focus::Toc);
     }
 
-    fn scroll_up(&mut self, increment: u16) {
-        self.page_scroll += if self.page_scroll > 0 { increment } else { 0 }
+    fn set_focus(&mut self, focus: Focus) {
+        self.accept_reverse_search();
+        if focus == Focus::Toc {
+            let rendered = self.rendered();
+            let section = self
+                .current_tab()
+                .section_at(&rendered, self.current_tab().scroll);
+            let tab = self.current_tab();
+            let selected = if tab.toc.items.is_empty() {
+                None
+            } else {
+                Some(section.unwrap_or(0))
+            };
+            self.current_tab_mut().toc.state.select(selected);
+        }
+        self.focus = focus;
+    }
+
+    /// Clicks focus the panel under the pointer and select what was clicked,
+    /// the wheel scrolls that panel.
+    pub fn on_mouse(&mut self, event: MouseEvent) {
+        // Popups and the command line keep the focus
+        if self.error.is_some() || self.picker.is_some() || self.command.is_some() {
+            return;
+        }
+        match event {
+            MouseEvent::Down(MouseButton::Left, x, y, _) => self.on_click(x, y),
+            MouseEvent::ScrollUp(x, y, _) => self.on_wheel(x, y, false),
+            MouseEvent::ScrollDown(x, y, _) => self.on_wheel(x, y, true),
+            _ => {}
+        }
+    }
+
+    /// Select the search result or section clicked, opening it on a double
+    /// click, or follow the link clicked.
+    fn on_click(&mut self, x: u16, y: u16) {
+        let double = self.panels.click(Instant::now(), x, y);
+        let focus = match self.panels.at(x, y) {
+            Some(focus) => focus,
+            None => return,
+        };
+        if focus != self.focus {
+            self.set_focus(focus);
+        }
+        let (column, row) = match self.panels.inner(focus, x, y) {
+            Some(position) => position,
+            None => return,
+        };
+        match focus {
+            Focus::Browse => {
+                if let Some(i) = self.page_at(row) {
+                    self.pages.state.select(Some(i));
+                    self.load_more();
+                    if double {
+                        self.on_enter();
+                    }
+                }
+            }
+            Focus::Toc => {
+                let toc = &mut self.current_tab_mut().toc;
+                let i = toc.offset + row;
+                if i < toc.items.len() {
+                    toc.state.select(Some(i));
+                    if double {
+                        self.on_enter();
+                    }
+                }
+            }
+            Focus::Read => {
+                if let Some(link) = self.link_at(column, row) {
+                    self.current_tab_mut().link = Some(link);
+                    self.open_link(false);
+                }
+            }
+            Focus::Search | Focus::Url => {}
+        }
+    }
+
+    fn on_wheel(&mut self, x: u16, y: u16, down: bool) {
+        match self.panels.at(x, y) {
+            Some(Focus::Browse) => {
+                self.pages.shift(down, 1);
+                self.load_more();
+            }
+            Some(Focus::Toc) => {
+                // The Table of Content only has a selection of its own in Toc mode
+                if self.focus != Focus::Toc {
+                    self.set_focus(Focus::Toc);
+                }
+                self.current_tab_mut().toc.shift(down, 1);
+            }
+            Some(Focus::Read) if down => self.scroll_down(WHEEL_ROWS),
+            Some(Focus::Read) => self.scroll_up(WHEEL_ROWS),
+            _ => {}
+        }
     }
 
-    fn scroll_down(&mut self, increment: u16) {
-        self.page_scroll -= if self.page_scroll < self.page.len() as u16 {
-            increment
+    /// Index of the search result on `row` of the Page list
+    fn page_at(&self, row: usize) -> Option<usize> {
+        let mut top = 0;
+        for (i, result) in self.pages.items.iter().enumerate().skip(self.pages.offset) {
+            // Full-text results take two rows
+            top += if result.snippet.is_empty() { 1 } else { 2 };
+            if row < top {
+                return Some(i);
+            }
+        }
+        None
+    }
+
+    /// Link at `column` of `row` in the Read pane
+    fn link_at(&mut self, column: usize, row: usize) -> Option<usize> {
+        let rendered = self.rendered();
+        let line = rendered.lines.get(self.current_tab().scroll + row)?;
+        let mut right = 0;
+        for span in line {
+            right += span.text.width();
+            if column < right {
+                return span.link;
+            }
+        }
+        None
+    }
+
+    fn go_back(&mut self) {
+        if self.current_tab_mut().go_back() {
+            self.status.clear();
         } else {
-            0
+            self.status = "No previous article".to_owned();
         }
     }
+
+    fn go_forward(&mut self) {
+        if self.current_tab_mut().go_forward() {
+            self.status.clear();
+        } else {
+            self.status = "No next article".to_owned();
+        }
+    }
+
+    /// Select the next or previous internal link, starting from the first one
+    /// on screen, and scroll it into view.
+    fn select_link(&mut self, forward: bool) {
+        let page_height = self.page_height as usize;
+        let rendered = self.rendered();
+        let links = &rendered.links;
+        let tab = &mut self.tabs[self.tab];
+        if links.is_empty() {
+            self.status = "This article has no links".to_owned();
+            return;
+        }
+        let scroll = tab.scroll;
+        let i = match tab.link {
+            Some(i) if forward => (i + 1) % links.len(),
+            Some(i) => (i + links.len() - 1) % links.len(),
+            None => links
+                .iter()
+                .position(|(_, line)| *line >= scroll)
+                .unwrap_or(0),
+        };
+        tab.link = Some(i);
+        let line = links[i].1;
+        if line < scroll || line >= scroll + page_height {
+            tab.scroll = line;
+        }
+        self.status = format!("Link to \"{}\"", links[i].0);
+    }
+
+    /// Find the query again after it changed, jumping to the first match on
+    /// screen or below if `jump` is set.
+    fn update_find(&mut self, jump: bool) {
+        let rendered = self.rendered();
+        let scroll = self.current_tab().scroll;
+        if let Some(find) = &mut self.find {
+            find.update(&rendered);
+            if jump {
+                find.select_from(scroll);
+            }
+        }
+        self.show_match();
+    }
+
+    fn find_next(&mut self, forward: bool) {
+        let find = match &mut self.find {
+            Some(find) => find,
+            None => return,
+        };
+        if forward {
+            find.next();
+        } else {
+            find.previous();
+        }
+        self.show_match();
+    }
+
+    /// Scroll the current match into view.
+    fn show_match(&mut self) {
+        let row = match self.find.as_ref().and_then(Find::current_match) {
+            Some(m) => m.row,
+            None => return,
+        };
+        let scroll = self.current_tab().scroll;
+        let height = self.page_height as usize;
+        if row < scroll || row >= scroll + height {
+            self.scroll_to(row.saturating_sub(height / 3));
+        }
+    }
+
+    /// The open article laid out as rows of the Read pane. A find follows
+    /// it to other articles and widths.
+    pub fn rendered(&mut self) -> Rc<Rendered> {
+        let width = self.page_width as usize;
+        let rendered = self.tabs[self.tab].rendered(width, &self.theme);
+        if let Some(find) = &mut self.find {
+            find.refresh(&rendered);
+        }
+        rendered
+    }
+
+    /// Row of the Read pane the heading `fragment` points at is on.
+    fn section_row(&mut self, fragment: &str) -> Option<usize> {
+        self.rendered().heading_line(fragment)
+    }
+
+    /// Scroll the Read pane so `row` is at the top, or as close as possible
+    /// without scrolling past the end.
+    fn scroll_to(&mut self, row: usize) {
+        let rows = self.rendered().lines.len();
+        let max = rows.saturating_sub(self.page_height as usize);
+        self.current_tab_mut().scroll = row.min(max);
+    }
+
+    fn scroll_up(&mut self, rows: usize) {
+        let scroll = self.current_tab().scroll;
+        self.scroll_to(scroll.saturating_sub(rows));
+    }
+
+    fn scroll_down(&mut self, rows: usize) {
+        let scroll = self.current_tab().scroll;
+        self.scroll_to(scroll + rows);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn article(title: &str) -> Result<Box<Request>, Error> {
+        let mut req = Request::new(title, "en", Endpoint::PageParse);
+        req.title = title.to_owned();
+        req.page = wikitext::parse("Text about [[Iron]]");
+        Ok(Box::new(req))
+    }
+
+    fn results(titles: &[&str], next_offset: Option<u16>) -> Result<Box<Request>, Error> {
+        let mut req = Request::new("", "en", Endpoint::PrefixSearch);
+        req.page_list = titles
+            .iter()
+            .map(|title| SearchResult {
+                title: title.to_string(),
+                snippet: String::new(),
+                wordcount: None,
+                timestamp: None,
+            })
+            .collect();
+        req.next_offset = next_offset;
+        Ok(Box::new(req))
+    }
+
+    fn loading_id(app: &App) -> u64 {
+        app.loading.as_ref().map(|(id, _)| *id).unwrap()
+    }
+
+    #[test]
+    fn app_opens_articles_in_the_tab_that_asked() {
+        let mut app = App::new(Config::default(), Worker::offline(), None);
+
+        // Switching tabs while an article loads
+        app.load_page("Rust".to_owned(), "en".to_owned());
+        let id = loading_id(&app);
+        app.new_tab();
+        app.on_response(id, article("Rust"));
+        assert_eq!(app.tabs[0].title, "Rust");
+        assert!(app.tabs[1].is_empty());
+        assert_eq!(app.tab, 1);
+
+        // Closing the tab it was for
+        app.load_page("Iron".to_owned(), "en".to_owned());
+        let id = loading_id(&app);
+        app.close_tab();
+        app.on_response(id, article("Iron"));
+        assert_eq!(app.tabs.len(), 1);
+        assert_eq!(app.tabs[0].title, "Rust");
+
+        // Cancelling an article opened in a new tab
+        app.new_tab();
+        let tab = app.current_tab().id;
+        app.request(Loading::Page {
+            title: "Oxide".to_owned(),
+            lang: "en".to_owned(),
+            tab,
+            new_tab: true,
+        });
+        app.switch_tab(0);
+        app.on_escape();
+        assert_eq!(app.tabs.len(), 1);
+        assert_eq!(app.tab, 0);
+    }
+
+    #[test]
+    fn app_keeps_focus_under_the_command_line() {
+        let mut app = App::new(Config::default(), Worker::offline(), None);
+        app.focus = Focus::Read;
+
+        app.on_action(Action::Command);
+        assert_eq!(app.mode(), Mode::Command);
+        assert_eq!(app.focus, Focus::Read);
+        app.on_tab();
+        assert_eq!(app.focus, Focus::Read);
+
+        app.on_escape();
+        assert_eq!(app.mode(), Mode::Read);
+        app.on_tab();
+        assert_eq!(app.mode(), Mode::Url);
+    }
+
+    #[test]
+    fn app_opens_links_in_a_new_tab_without_leaving_read() {
+        let mut app = App::new(Config::default(), Worker::offline(), None);
+        app.page_width = 80;
+        app.load_page("Rust".to_owned(), "en".to_owned());
+        let id = loading_id(&app);
+        app.on_response(id, article("Rust"));
+        assert_eq!(app.focus, Focus::Read);
+
+        app.on_action(Action::NextLink);
+        app.on_action(Action::OpenInNewTab);
+        assert_eq!(app.tabs.len(), 2);
+        assert_eq!(app.tab, 1);
+        assert_eq!(app.focus, Focus::Read);
+
+        // Enter while it loads does not cancel it
+        app.on_action(Action::Enter);
+        let id = loading_id(&app);
+        app.on_response(id, article("Iron"));
+        assert_eq!(app.tabs[1].title, "Iron");
+    }
+
+    #[test]
+    fn app_drops_more_results_of_a_replaced_list() {
+        let mut app = App::new(Config::default(), Worker::offline(), None);
+        app.request(Loading::Search {
+            search: "rust".to_owned(),
+            full_text: false,
+        });
+        let id = loading_id(&app);
+        app.on_response(id, results(&["Rust"], Some(30)));
+        app.focus = Focus::Search;
+        app.load_more();
+        let more = loading_id(&app);
+
+        // Suggestions for new input replace the list while more loads
+        "iron".chars().for_each(|c| app.on_key(c));
+        app.suggest_pages();
+        let id = app.suggesting.as_ref().map(|(id, _)| *id).unwrap();
+        app.on_response(id, results(&["Iron"], Some(10)));
+        assert!(app.loading.is_none());
+        app.on_response(more, results(&["Rusty"], Some(60)));
+        let titles: Vec<_> = app.pages.items.iter().map(|page| &page.title).collect();
+        assert_eq!(titles, ["Iron"]);
+        assert_eq!(app.pages_next, Some(10));
+    }
 }
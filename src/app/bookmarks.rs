@@ -0,0 +1,47 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// Saved articles, one `lang:title` per line
+#[derive(Default)]
+pub struct Bookmarks {
+    pub entries: Vec<(String, String)>,
+    path: Option<PathBuf>,
+}
+
+impl Bookmarks {
+    /// Read the bookmarks saved at `path`, a missing file means none.
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let entries = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|text| {
+                text.lines()
+                    .filter_map(|line| line.split_once(':'))
+                    .map(|(lang, title)| (lang.to_owned(), title.to_owned()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { entries, path }
+    }
+
+    /// Save `title`, returning false if it already was.
+    pub fn add(&mut self, lang: &str, title: &str) -> io::Result<bool> {
+        if self.entries.iter().any(|(l, t)| l == lang && t == title) {
+            return Ok(false);
+        }
+        self.entries.push((lang.to_owned(), title.to_owned()));
+        if let Some(path) = &self.path {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let text = self
+                .entries
+                .iter()
+                .map(|(lang, title)| format!("{}:{}\n", lang, title))
+                .collect::<String>();
+            fs::write(path, text)?;
+        }
+        Ok(true)
+    }
+}
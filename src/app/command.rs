@@ -0,0 +1,173 @@
+use crate::app::input::Input;
+
+/// Command names and the argument they take
+pub const COMMANDS: [(&str, &str); 8] = [
+    ("open", "<title>"),
+    ("lang", "<code>"),
+    ("random", ""),
+    ("export", "md"),
+    ("bookmark", ""),
+    ("bookmarks", ""),
+    ("set", "theme <name>"),
+    ("quit", ""),
+];
+
+/// A line typed after `:`
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Open(String),
+    Lang(String),
+    Random,
+    /// Write the open article to a Markdown file
+    Export,
+    Bookmark,
+    /// Pick an article among the bookmarks
+    Bookmarks,
+    SetTheme(String),
+    Quit,
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let line = line.trim();
+        let (name, arg) = match line.split_once(' ') {
+            Some((name, arg)) => (name, arg.trim()),
+            None => (line, ""),
+        };
+        let usage = || {
+            let (_, usage) = COMMANDS.iter().find(|(n, _)| *n == name).unwrap();
+            Err(format!("Usage: :{} {}", name, usage))
+        };
+        let command = match name {
+            "open" if !arg.is_empty() => Command::Open(arg.to_owned()),
+            "lang" if !arg.is_empty() => Command::Lang(arg.to_owned()),
+            "random" => Command::Random,
+            "export" => match arg {
+                "md" | "" => Command::Export,
+                _ => return Err(format!("Cannot export to '{}', only md is supported", arg)),
+            },
+            "bookmark" => Command::Bookmark,
+            "bookmarks" => Command::Bookmarks,
+            "set" => match arg.split_once(' ') {
+                Some(("theme", theme)) => Command::SetTheme(theme.trim().to_owned()),
+                _ => return usage(),
+            },
+            "quit" | "q" => Command::Quit,
+            "open" | "lang" => return usage(),
+            _ => return Err(format!("Unknown command '{}'", name)),
+        };
+        Ok(command)
+    }
+}
+
+/// The `:` command line shown on the status row
+#[derive(Default)]
+pub struct CommandLine {
+    pub input: Input,
+    /// Why the last command failed, cleared by editing
+    pub error: Option<String>,
+    completions: Vec<String>,
+    completion: usize,
+}
+
+impl CommandLine {
+    /// Replace the input with the next (or previous) line completing it,
+    /// `arguments` giving the arguments a command can take.
+    pub fn complete(&mut self, forward: bool, arguments: impl FnOnce(&str) -> Vec<String>) {
+        let cycling = self
+            .completions
+            .get(self.completion)
+            .is_some_and(|completion| completion == self.input.as_str());
+        if cycling {
+            let len = self.completions.len();
+            self.completion = if forward {
+                (self.completion + 1) % len
+            } else {
+                (self.completion + len - 1) % len
+            };
+        } else {
+            self.completions = complete(self.input.as_str(), arguments);
+            self.completion = if forward {
+                0
+            } else {
+                self.completions.len().saturating_sub(1)
+            };
+        }
+        if let Some(completion) = self.completions.get(self.completion) {
+            self.input.set(completion);
+        }
+    }
+}
+
+/// Lines starting like `line`: command names, then their arguments.
+fn complete(line: &str, arguments: impl FnOnce(&str) -> Vec<String>) -> Vec<String> {
+    match line.split_once(' ') {
+        None => COMMANDS
+            .iter()
+            .filter(|(name, _)| name.starts_with(line))
+            .map(|(name, arg)| {
+                if arg.is_empty() {
+                    name.to_string()
+                } else {
+                    format!("{} ", name)
+                }
+            })
+            .collect(),
+        Some((name, arg)) => {
+            let arg = arg.trim_start().to_lowercase();
+            let mut completions = arguments(name)
+                .into_iter()
+                .filter(|candidate| candidate.to_lowercase().starts_with(&arg))
+                .map(|candidate| format!("{} {}", name, candidate))
+                .collect::<Vec<_>>();
+            completions.sort();
+            completions.dedup();
+            completions
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_parse() {
+        assert_eq!(
+            Command::parse("open  Rust (fungus) "),
+            Ok(Command::Open("Rust (fungus)".to_owned()))
+        );
+        assert_eq!(
+            Command::parse("set theme light"),
+            Ok(Command::SetTheme("light".to_owned()))
+        );
+        assert_eq!(Command::parse("export md"), Ok(Command::Export));
+        assert_eq!(Command::parse("q"), Ok(Command::Quit));
+        assert_eq!(
+            Command::parse("lang"),
+            Err("Usage: :lang <code>".to_owned())
+        );
+        assert!(Command::parse("export pdf").is_err());
+        assert!(Command::parse("explode").is_err());
+    }
+
+    #[test]
+    fn command_line_complete() {
+        let titles = |name: &str| match name {
+            "open" => vec!["Rust".to_owned(), "rustc".to_owned(), "Iron".to_owned()],
+            _ => Vec::new(),
+        };
+        assert_eq!(complete("b", titles), vec!["bookmark", "bookmarks"]);
+        let mut line = CommandLine::default();
+        "open ru".chars().for_each(|c| line.input.insert(c));
+        line.complete(true, titles);
+        assert_eq!(line.input.as_str(), "open Rust");
+        line.complete(true, titles);
+        assert_eq!(line.input.as_str(), "open rustc");
+        line.complete(true, titles);
+        assert_eq!(line.input.as_str(), "open Rust");
+        line.input.backspace();
+        line.complete(false, titles);
+        assert_eq!(line.input.as_str(), "open rustc");
+    }
+}
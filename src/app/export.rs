@@ -0,0 +1,76 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Write `text` to `<title>.md` in `dir`, numbering the name rather than
+/// overwriting an earlier export. Returns the path written.
+pub fn export(dir: &Path, title: &str, text: &str) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let name = file_name(title);
+    for n in 1.. {
+        let path = match n {
+            1 => dir.join(format!("{}.md", name)),
+            _ => dir.join(format!("{} ({}).md", name, n)),
+        };
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                file.write_all(text.as_bytes())?;
+                return Ok(path);
+            }
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err),
+        }
+    }
+    unreachable!()
+}
+
+/// `title` with the characters file systems reject or treat specially
+/// replaced, e.g. `AC/DC` becomes `AC_DC`.
+fn file_name(title: &str) -> String {
+    let name: String = title
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    // No hidden files, and Windows drops trailing dots and spaces
+    let name = name.trim_matches(|c| c == '.' || c == ' ');
+    if name.is_empty() {
+        "article".to_owned()
+    } else {
+        name.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_file_name() {
+        assert_eq!(
+            file_name("Rust (programming language)"),
+            "Rust (programming language)"
+        );
+        assert_eq!(file_name("AC/DC"), "AC_DC");
+        assert_eq!(file_name(r"C:\Windows"), "C__Windows");
+        assert_eq!(file_name("../..."), "_");
+        assert_eq!(file_name(".."), "article");
+    }
+
+    #[test]
+    fn export_does_not_overwrite() {
+        let dir = std::env::temp_dir().join(format!("wiki-export-{}", std::process::id()));
+        let first = export(&dir, "Iron", "one").unwrap();
+        let second = export(&dir, "Iron", "two").unwrap();
+        let first_text = fs::read_to_string(&first).unwrap();
+        let second_text = fs::read_to_string(&second).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(first, dir.join("Iron.md"));
+        assert_eq!(second, dir.join("Iron (2).md"));
+        assert_eq!(first_text, "one");
+        assert_eq!(second_text, "two");
+    }
+}
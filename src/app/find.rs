@@ -0,0 +1,238 @@
+use crate::ui::Rendered;
+use regex::{Regex, RegexBuilder};
+use std::rc::{Rc, Weak};
+
+/// A `/` search in the open article
+#[derive(Default)]
+pub struct Find {
+    pub query: String,
+    pub case_sensitive: bool,
+    /// Take the query as a regular expression rather than plain text
+    pub regex: bool,
+    /// The query is still being typed
+    pub editing: bool,
+    /// Parts of the matches in the Read pane, one per row they are on
+    pub matches: Vec<Match>,
+    /// Number of matches
+    pub count: usize,
+    /// Index of the match last jumped to
+    pub current: usize,
+    /// Why the query is not a valid regex
+    pub error: Option<String>,
+    /// Layout the matches were found in
+    searched: Weak<Rendered>,
+}
+
+/// The part of a match within one row of the Read pane, as byte offsets in
+/// its text
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Match {
+    pub row: usize,
+    pub start: usize,
+    pub end: usize,
+    /// Index of the match this is part of
+    pub index: usize,
+}
+
+impl Find {
+    pub fn new() -> Self {
+        Self {
+            editing: true,
+            ..Self::default()
+        }
+    }
+
+    fn pattern(&self) -> Result<Regex, regex::Error> {
+        let pattern = if self.regex {
+            self.query.clone()
+        } else {
+            regex::escape(&self.query)
+        };
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!self.case_sensitive)
+            .build()
+    }
+
+    /// Look for the query in `rendered` again, after the query changed.
+    /// Rows wrapped from one line are searched as that line.
+    pub fn update(&mut self, rendered: &Rc<Rendered>) {
+        self.searched = Rc::downgrade(rendered);
+        self.matches.clear();
+        self.count = 0;
+        self.error = None;
+        if self.query.is_empty() {
+            return;
+        }
+        let pattern = match self.pattern() {
+            Ok(pattern) => pattern,
+            Err(err) => {
+                self.error = Some(err.to_string());
+                return;
+            }
+        };
+        let mut row = 0;
+        while row < rendered.lines.len() {
+            // The line, and where each of its rows is in it
+            let mut text = String::new();
+            let mut rows = Vec::new();
+            loop {
+                let start = text.len();
+                text.extend(rendered.lines[row].iter().map(|span| span.text.as_str()));
+                rows.push((row, start, text.len()));
+                row += 1;
+                match rendered.joins.get(row) {
+                    Some(Some(join)) => text.push_str(join),
+                    _ => break,
+                }
+            }
+            for m in pattern.find_iter(&text).filter(|m| !m.as_str().is_empty()) {
+                let parts = self.matches.len();
+                for &(row, start, end) in &rows {
+                    if m.start() < end && m.end() > start {
+                        self.matches.push(Match {
+                            row,
+                            start: m.start().max(start) - start,
+                            end: m.end().min(end) - start,
+                            index: self.count,
+                        });
+                    }
+                }
+                // Spaces cut at a row break are not shown
+                if self.matches.len() > parts {
+                    self.count += 1;
+                }
+            }
+        }
+        self.current = self.current.min(self.count.saturating_sub(1));
+    }
+
+    /// Find the query again if `rendered` is a new layout of the article.
+    pub fn refresh(&mut self, rendered: &Rc<Rendered>) {
+        if !Weak::ptr_eq(&self.searched, &Rc::downgrade(rendered)) {
+            self.update(rendered);
+        }
+    }
+
+    /// Make the first match at or after `row` the current one.
+    pub fn select_from(&mut self, row: usize) {
+        self.current = self
+            .matches
+            .iter()
+            .find(|m| m.row >= row)
+            .map_or(0, |m| m.index);
+    }
+
+    pub fn next(&mut self) {
+        if self.count > 0 {
+            self.current = (self.current + 1) % self.count;
+        }
+    }
+
+    pub fn previous(&mut self) {
+        if self.count > 0 {
+            self.current = (self.current + self.count - 1) % self.count;
+        }
+    }
+
+    /// First part of the current match
+    pub fn current_match(&self) -> Option<Match> {
+        self.matches
+            .iter()
+            .find(|m| m.index == self.current)
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::{self, Theme};
+    use crate::wikitext;
+
+    fn rendered(wikitext: &str) -> Rc<Rendered> {
+        Rc::new(ui::render(&wikitext::parse(wikitext), &Theme::default()))
+    }
+
+    #[test]
+    fn find_plain_text_ignoring_case() {
+        let rendered = rendered("Rust and ''rust''.\n\nRUST (1+1)");
+        let mut find = Find::new();
+        find.query = "rust".to_owned();
+        find.update(&rendered);
+        assert_eq!(find.matches.len(), 3);
+        assert_eq!(
+            find.matches[1],
+            Match {
+                row: 0,
+                start: 9,
+                end: 13,
+                index: 1
+            }
+        );
+        find.case_sensitive = true;
+        find.update(&rendered);
+        assert_eq!(find.matches.len(), 1);
+        find.query = "(1+1)".to_owned();
+        find.update(&rendered);
+        assert_eq!(find.matches.len(), 1);
+    }
+
+    #[test]
+    fn find_regex() {
+        let rendered = rendered("cat cot cut\n\ncoat");
+        let mut find = Find::new();
+        find.regex = true;
+        find.query = "c.t".to_owned();
+        find.update(&rendered);
+        assert_eq!(find.matches.len(), 3);
+        find.select_from(1);
+        assert_eq!(find.current, 0);
+        find.previous();
+        assert_eq!(find.current_match().unwrap().start, 8);
+        find.query = "c(".to_owned();
+        find.update(&rendered);
+        assert!(find.error.is_some());
+        assert!(find.matches.is_empty());
+    }
+
+    #[test]
+    fn find_across_wrapped_rows() {
+        let document = wikitext::parse("One two three\n\nabcdefghijkl");
+        let rendered = Rc::new(ui::render(&document, &Theme::default()).wrap(9));
+        let mut find = Find::new();
+        find.query = "two three".to_owned();
+        find.update(&rendered);
+        assert_eq!(find.count, 1);
+        assert_eq!(
+            find.matches,
+            vec![
+                Match {
+                    row: 0,
+                    start: 4,
+                    end: 7,
+                    index: 0
+                },
+                Match {
+                    row: 1,
+                    start: 0,
+                    end: 5,
+                    index: 0
+                }
+            ]
+        );
+        find.query = "hijk".to_owned();
+        find.update(&rendered);
+        assert_eq!(find.count, 1);
+        assert_eq!(find.matches.len(), 2);
+        find.query = "one".to_owned();
+        find.update(&rendered);
+        assert_eq!(find.count, 1);
+        // A new layout is searched again, the same one is not
+        find.query = "three".to_owned();
+        find.refresh(&rendered);
+        assert_eq!(find.current_match().unwrap().start, 0);
+        find.refresh(&Rc::new(ui::render(&document, &Theme::default())));
+        assert_eq!(find.current_match().unwrap().row, 0);
+        assert_eq!(find.current_match().unwrap().start, 8);
+    }
+}
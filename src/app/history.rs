@@ -0,0 +1,90 @@
+use crate::request::LangLink;
+use crate::wikitext::Document;
+
+/// Most articles kept on each side of the history
+const MAX_LEN: usize = 100;
+
+/// An article as the reader left it
+pub struct Visit {
+    pub title: String,
+    pub page: Document,
+    pub toc: Vec<(String, String)>,
+    pub langlinks: Vec<LangLink>,
+    pub lang: String,
+    pub url: String,
+    pub scroll: usize,
+}
+
+/// Browser-style back and forward stacks of visited articles
+#[derive(Default)]
+pub struct History {
+    back: Vec<Visit>,
+    forward: Vec<Visit>,
+}
+
+impl History {
+    /// Record the article being left for another one. Forward history is
+    /// dropped, as in a browser.
+    pub fn push(&mut self, visit: Visit) {
+        self.back.push(visit);
+        if self.back.len() > MAX_LEN {
+            self.back.remove(0);
+        }
+        self.forward.clear();
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        !self.back.is_empty()
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        !self.forward.is_empty()
+    }
+
+    /// Swap `current` for the previous article.
+    pub fn back(&mut self, current: Visit) -> Option<Visit> {
+        let visit = self.back.pop()?;
+        self.forward.push(current);
+        Some(visit)
+    }
+
+    /// Swap `current` for the next article.
+    pub fn forward(&mut self, current: Visit) -> Option<Visit> {
+        let visit = self.forward.pop()?;
+        self.back.push(current);
+        Some(visit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn visit(url: &str) -> Visit {
+        Visit {
+            title: String::new(),
+            page: Document::default(),
+            toc: Vec::new(),
+            langlinks: Vec::new(),
+            lang: "en".to_owned(),
+            url: url.to_owned(),
+            scroll: 0,
+        }
+    }
+
+    #[test]
+    fn history_back_and_forward() {
+        let mut history = History::default();
+        history.push(visit("a"));
+        history.push(visit("b"));
+        let current = history.back(visit("c")).unwrap();
+        assert_eq!(current.url, "b");
+        let current = history.back(current).unwrap();
+        assert_eq!(current.url, "a");
+        assert!(!history.can_go_back());
+        let current = history.forward(current).unwrap();
+        assert_eq!(current.url, "b");
+        history.push(current);
+        assert!(!history.can_go_forward());
+    }
+}
@@ -0,0 +1,183 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// A single line text field. The cursor sits on a grapheme boundary and the
+/// view scrolls horizontally to keep it visible.
+#[derive(Default)]
+pub struct Input {
+    text: String,
+    /// Byte offset of the cursor in `text`
+    cursor: usize,
+    /// First column shown, set by `view`
+    scroll: usize,
+}
+
+impl Input {
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Replace the text, with the cursor at its end.
+    pub fn set(&mut self, text: &str) {
+        self.text = text.to_owned();
+        self.cursor = self.text.len();
+    }
+
+    pub fn insert(&mut self, c: char) {
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+
+    pub fn backspace(&mut self) {
+        let start = self.previous_boundary();
+        self.text.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    pub fn delete(&mut self) {
+        let end = self.next_boundary();
+        self.text.replace_range(self.cursor..end, "");
+    }
+
+    /// Delete the word before the cursor, like Ctrl-W in a shell.
+    pub fn delete_word(&mut self) {
+        let start = self.previous_word();
+        self.text.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    /// Delete everything before the cursor.
+    pub fn delete_to_start(&mut self) {
+        self.text.replace_range(..self.cursor, "");
+        self.cursor = 0;
+    }
+
+    pub fn left(&mut self) {
+        self.cursor = self.previous_boundary();
+    }
+
+    pub fn right(&mut self) {
+        self.cursor = self.next_boundary();
+    }
+
+    pub fn word_left(&mut self) {
+        self.cursor = self.previous_word();
+    }
+
+    pub fn word_right(&mut self) {
+        self.cursor = self.next_word();
+    }
+
+    pub fn home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.cursor = self.text.len();
+    }
+
+    fn previous_boundary(&self) -> usize {
+        self.text[..self.cursor]
+            .grapheme_indices(true)
+            .next_back()
+            .map_or(0, |(i, _)| i)
+    }
+
+    fn next_boundary(&self) -> usize {
+        self.text[self.cursor..]
+            .graphemes(true)
+            .next()
+            .map_or(self.cursor, |g| self.cursor + g.len())
+    }
+
+    /// Start of the word before the cursor, skipping spaces in between
+    fn previous_word(&self) -> usize {
+        let before = self.text[..self.cursor].trim_end();
+        before
+            .unicode_word_indices()
+            .next_back()
+            .map_or(0, |(i, _)| i)
+    }
+
+    /// End of the word after the cursor
+    fn next_word(&self) -> usize {
+        self.text[self.cursor..]
+            .unicode_word_indices()
+            .next()
+            .map_or(self.text.len(), |(i, word)| self.cursor + i + word.len())
+    }
+
+    /// The part of the text that fits in `width` columns and the column of
+    /// the cursor within it.
+    pub fn view(&mut self, width: usize) -> (&str, usize) {
+        let column = self.text[..self.cursor].width();
+        if column < self.scroll {
+            self.scroll = column;
+        } else if width > 0 && column >= self.scroll + width {
+            self.scroll = column + 1 - width;
+        }
+        let mut start = self.text.len();
+        let mut end = self.text.len();
+        let mut x = 0;
+        for (i, grapheme) in self.text.grapheme_indices(true) {
+            if x >= self.scroll && start == self.text.len() {
+                start = i;
+            }
+            x += grapheme.width();
+            if x > self.scroll + width {
+                end = i;
+                break;
+            }
+        }
+        let skipped = self.text[..start].width();
+        (&self.text[start..end.max(start)], column - skipped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(text: &str) -> Input {
+        let mut input = Input::default();
+        text.chars().for_each(|c| input.insert(c));
+        input
+    }
+
+    #[test]
+    fn input_edits_at_cursor() {
+        let mut input = typed("Caf Olé");
+        input.word_left();
+        input.left();
+        input.insert('é');
+        assert_eq!(input.as_str(), "Café Olé");
+        input.end();
+        input.backspace();
+        assert_eq!(input.as_str(), "Café Ol");
+        input.delete_word();
+        assert_eq!(input.as_str(), "Café ");
+        input.home();
+        input.word_right();
+        input.delete();
+        assert_eq!(input.as_str(), "Café");
+        input.left();
+        input.delete_to_start();
+        assert_eq!(input.as_str(), "é");
+    }
+
+    #[test]
+    fn input_view_scrolls_to_cursor() {
+        let mut input = typed("日本語 wiki");
+        assert_eq!(input.view(20), ("日本語 wiki", 11));
+        assert_eq!(input.view(6), (" wiki", 5));
+        input.home();
+        assert_eq!(input.view(6), ("日本語", 0));
+        input.right();
+        input.right();
+        assert_eq!(input.view(6), ("日本語", 4));
+    }
+}
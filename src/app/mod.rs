@@ -1,2 +1,14 @@
+#[allow(clippy::module_inception)]
 mod app;
-pub use app::{App, Mode};
+mod bookmarks;
+mod command;
+mod export;
+mod find;
+mod history;
+mod input;
+mod mouse;
+mod picker;
+mod queries;
+mod tab;
+pub use app::{App, Focus, Loading, Mode};
+pub use find::Match;
@@ -0,0 +1,104 @@
+use crate::app::Focus;
+use std::time::{Duration, Instant};
+use tui::layout::Rect;
+
+/// Longest pause between the two clicks of a double click
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+/// Where each panel was last drawn, to find the one under the pointer
+#[derive(Default)]
+pub struct Panels {
+    pub search: Rect,
+    pub pages: Rect,
+    pub toc: Rect,
+    pub page: Rect,
+    pub url: Rect,
+    /// Last click and where it was, to tell double clicks
+    last_click: Option<(Instant, u16, u16)>,
+}
+
+impl Panels {
+    /// The panel at column `x` and row `y` of the terminal.
+    pub fn at(&self, x: u16, y: u16) -> Option<Focus> {
+        [
+            (self.search, Focus::Search),
+            (self.pages, Focus::Browse),
+            (self.toc, Focus::Toc),
+            (self.page, Focus::Read),
+            (self.url, Focus::Url),
+        ]
+        .iter()
+        .find(|(area, _)| contains(*area, x, y))
+        .map(|(_, focus)| *focus)
+    }
+
+    /// Column and row of `x` and `y` inside the borders of the `focus` panel.
+    pub fn inner(&self, focus: Focus, x: u16, y: u16) -> Option<(usize, usize)> {
+        let area = match focus {
+            Focus::Search => self.search,
+            Focus::Browse => self.pages,
+            Focus::Toc => self.toc,
+            Focus::Read => self.page,
+            Focus::Url => self.url,
+        };
+        let inner = Rect::new(
+            area.x + 1,
+            area.y + 1,
+            area.width.saturating_sub(2),
+            area.height.saturating_sub(2),
+        );
+        if contains(inner, x, y) {
+            Some(((x - inner.x) as usize, (y - inner.y) as usize))
+        } else {
+            None
+        }
+    }
+
+    /// Record a click, returning whether it completes a double click.
+    pub fn click(&mut self, now: Instant, x: u16, y: u16) -> bool {
+        let double = match self.last_click {
+            Some((at, last_x, last_y)) => {
+                (last_x, last_y) == (x, y) && now.duration_since(at) <= DOUBLE_CLICK
+            }
+            None => false,
+        };
+        // A third click starts over
+        self.last_click = if double { None } else { Some((now, x, y)) };
+        double
+    }
+}
+
+fn contains(area: Rect, x: u16, y: u16) -> bool {
+    x >= area.left() && x < area.right() && y >= area.top() && y < area.bottom()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panels_at() {
+        let panels = Panels {
+            pages: Rect::new(0, 3, 30, 10),
+            page: Rect::new(31, 0, 70, 20),
+            ..Panels::default()
+        };
+        assert_eq!(panels.at(0, 3), Some(Focus::Browse));
+        assert_eq!(panels.at(30, 3), None);
+        assert_eq!(panels.at(100, 19), Some(Focus::Read));
+        assert_eq!(panels.inner(Focus::Read, 33, 1), Some((1, 0)));
+        assert_eq!(panels.inner(Focus::Read, 31, 1), None);
+        assert_eq!(panels.inner(Focus::Browse, 5, 12), None);
+    }
+
+    #[test]
+    fn panels_double_click() {
+        let mut panels = Panels::default();
+        let now = Instant::now();
+        assert!(!panels.click(now, 4, 5));
+        assert!(panels.click(now + Duration::from_millis(200), 4, 5));
+        assert!(!panels.click(now + Duration::from_millis(300), 4, 5));
+        assert!(!panels.click(now + Duration::from_millis(900), 4, 5));
+        assert!(!panels.click(now + Duration::from_millis(1000), 5, 5));
+    }
+}
@@ -0,0 +1,28 @@
+use crate::util::StatefulList;
+
+/// What choosing an entry of a `Picker` does
+pub enum PickerKind {
+    /// Switch the Wikipedia edition, one language code per entry
+    Language(Vec<String>),
+    /// Open an article, one `(lang, title)` per entry
+    Article(Vec<(String, String)>),
+}
+
+/// A popup list the user picks one entry from
+pub struct Picker {
+    pub title: String,
+    pub list: StatefulList<String>,
+    pub kind: PickerKind,
+}
+
+impl Picker {
+    pub fn new(title: &str, items: Vec<String>, kind: PickerKind) -> Self {
+        let mut list = StatefulList::with_items(items);
+        list.state.select(Some(0));
+        Self {
+            title: title.to_owned(),
+            list,
+            kind,
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// Entries kept, older queries are forgotten first
+const MAX_LEN: usize = 500;
+
+/// Submitted search queries, oldest first, saved one per line
+#[derive(Default)]
+pub struct QueryHistory {
+    entries: Vec<String>,
+    path: Option<PathBuf>,
+    /// Entry shown by Up and Down, if the input still holds it
+    recall: Option<usize>,
+    /// Input from before Up was first pressed
+    draft: String,
+}
+
+/// State of a Ctrl-R search through the history
+#[derive(Default)]
+pub struct ReverseSearch {
+    pub query: String,
+    /// Index of the matching entry
+    pub found: Option<usize>,
+}
+
+impl QueryHistory {
+    /// Read the history saved at `path`, a missing file is an empty history.
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let entries = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|text| text.lines().map(str::to_owned).collect())
+            .unwrap_or_default();
+        Self {
+            entries,
+            path,
+            ..Self::default()
+        }
+    }
+
+    pub fn get(&self, i: usize) -> Option<&str> {
+        self.entries.get(i).map(String::as_str)
+    }
+
+    /// Add `query` as the newest entry and save the history.
+    pub fn push(&mut self, query: &str) -> io::Result<()> {
+        let query = query.replace('\n', " ");
+        self.entries.retain(|entry| *entry != query);
+        self.entries.push(query);
+        if self.entries.len() > MAX_LEN {
+            self.entries.drain(..self.entries.len() - MAX_LEN);
+        }
+        self.recall = None;
+        self.save()
+    }
+
+    fn save(&self) -> io::Result<()> {
+        if let Some(path) = &self.path {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let mut text = self.entries.join("\n");
+            text.push('\n');
+            fs::write(path, text)?;
+        }
+        Ok(())
+    }
+
+    /// The entry before the one recalled, `input` being what the user sees.
+    /// Recalling starts over from the newest entry once the input is edited.
+    pub fn older(&mut self, input: &str) -> Option<&str> {
+        let recalled = self.recall.and_then(|i| self.get(i));
+        let i = match self.recall {
+            Some(i) if recalled == Some(input) => i.checked_sub(1)?,
+            _ => {
+                self.draft = input.to_owned();
+                self.entries.len().checked_sub(1)?
+            }
+        };
+        self.recall = Some(i);
+        self.get(i)
+    }
+
+    /// The entry after the one recalled, then the input from before recalling.
+    pub fn newer(&mut self, input: &str) -> Option<&str> {
+        match self.recall {
+            Some(i) if self.get(i) == Some(input) => {
+                if i + 1 < self.entries.len() {
+                    self.recall = Some(i + 1);
+                    self.get(i + 1)
+                } else {
+                    self.recall = None;
+                    Some(&self.draft)
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Index of the newest entry before `before` containing `query`,
+    /// ignoring case.
+    pub fn find(&self, query: &str, before: usize) -> Option<usize> {
+        let query = query.to_lowercase();
+        self.entries[..before.min(self.entries.len())]
+            .iter()
+            .rposition(|entry| entry.to_lowercase().contains(&query))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(entries: &[&str]) -> QueryHistory {
+        let mut history = QueryHistory::default();
+        for entry in entries {
+            history.push(entry).unwrap();
+        }
+        history
+    }
+
+    #[test]
+    fn query_history_recall() {
+        let mut history = history(&["rust", "iron", "rust", "oxide"]);
+        assert_eq!(history.entries.len(), 3);
+        assert_eq!(history.newer("ox"), None);
+        assert_eq!(history.older("ox"), Some("oxide"));
+        assert_eq!(history.older("oxide"), Some("rust"));
+        assert_eq!(history.older("rust"), Some("iron"));
+        assert_eq!(history.older("iron"), None);
+        assert_eq!(history.newer("iron"), Some("rust"));
+        assert_eq!(history.newer("rust"), Some("oxide"));
+        assert_eq!(history.newer("oxide"), Some("ox"));
+        history.older("ox");
+        assert_eq!(history.older("oxide!"), Some("oxide"));
+    }
+
+    #[test]
+    fn query_history_find_and_save() {
+        let path = std::env::temp_dir().join(format!("wiki-queries-{}", std::process::id()));
+        let mut history = QueryHistory::load(Some(path.clone()));
+        history.push("Rust (fungus)").unwrap();
+        history.push("Iron").unwrap();
+        history.push("rustacean").unwrap();
+        let history = QueryHistory::load(Some(path.clone()));
+        fs::remove_file(path).unwrap();
+        assert_eq!(history.find("RUST", usize::MAX), Some(2));
+        assert_eq!(history.find("rust", 2), Some(0));
+        assert_eq!(history.find("rust", 0), None);
+        assert_eq!(history.find("copper", 3), None);
+    }
+}
@@ -0,0 +1,172 @@
+use crate::app::history::{History, Visit};
+use crate::request::LangLink;
+use crate::ui::{self, Rendered, Theme};
+use crate::util::StatefulList;
+use crate::wikitext::Document;
+use std::rc::Rc;
+
+/// An article open in the Read pane. Each tab keeps its own history.
+pub struct Tab {
+    /// Identifies the tab while others are opened and closed
+    pub id: u64,
+    /// Article title, shown in the tab bar
+    pub title: String,
+    pub page: Document,
+    /// `(label, anchor)` of each section
+    pub toc: StatefulList<(String, String)>,
+    pub langlinks: Vec<LangLink>,
+    /// Language edition of the article
+    pub lang: String,
+    pub url: String,
+    /// First row of the article on screen
+    pub scroll: usize,
+    /// Selected internal link
+    pub link: Option<usize>,
+    history: History,
+    /// Layout of the article and the width it was wrapped to
+    rendered: Option<(usize, Rc<Rendered>)>,
+}
+
+impl Tab {
+    /// A tab with no article yet
+    pub fn new(id: u64, lang: &str, url: String) -> Self {
+        Self {
+            id,
+            title: String::new(),
+            page: Document::default(),
+            toc: StatefulList::default(),
+            langlinks: Vec::new(),
+            lang: lang.to_owned(),
+            url,
+            scroll: 0,
+            link: None,
+            history: History::default(),
+            rendered: None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.page.blocks.is_empty()
+    }
+
+    /// The article laid out as rows `width` columns wide. The layout is kept
+    /// until the article or the width changes, or `forget_rendered`.
+    pub fn rendered(&mut self, width: usize, theme: &Theme) -> Rc<Rendered> {
+        match &self.rendered {
+            Some((cached, rendered)) if *cached == width => rendered.clone(),
+            _ => {
+                let rendered = Rc::new(ui::render(&self.page, theme).wrap(width));
+                self.rendered = Some((width, rendered.clone()));
+                rendered
+            }
+        }
+    }
+
+    /// Entry of the Table of Content for the section `row` of `rendered` is
+    /// in. Headings the API does not list belong to the section before.
+    pub fn section_at(&self, rendered: &Rendered, row: usize) -> Option<usize> {
+        let last = rendered.section_at(row)?;
+        rendered.headings[..=last]
+            .iter()
+            .rev()
+            .find_map(|(anchor, _)| self.toc.items.iter().position(|(_, a)| a == anchor))
+    }
+
+    /// Row of `rendered` the heading of the `i`th entry of the Table of
+    /// Content is on.
+    pub fn section_row(&self, rendered: &Rendered, i: usize) -> Option<usize> {
+        let (_, anchor) = self.toc.items.get(i)?;
+        rendered
+            .headings
+            .iter()
+            .find(|(a, _)| a == anchor)
+            .map(|(_, row)| *row)
+    }
+
+    /// Drop the layout, once the theme changed.
+    pub fn forget_rendered(&mut self) {
+        self.rendered = None;
+    }
+
+    /// Show a newly loaded article, the current one going to the history.
+    pub fn open(&mut self, visit: Visit) {
+        if !self.is_empty() {
+            let current = self.take_visit();
+            self.history.push(current);
+        }
+        self.restore_visit(visit);
+    }
+
+    /// Go back to the previous article, returns false if there is none.
+    pub fn go_back(&mut self) -> bool {
+        if !self.history.can_go_back() {
+            return false;
+        }
+        let current = self.take_visit();
+        if let Some(visit) = self.history.back(current) {
+            self.restore_visit(visit);
+        }
+        true
+    }
+
+    /// Go to the next article, returns false if there is none.
+    pub fn go_forward(&mut self) -> bool {
+        if !self.history.can_go_forward() {
+            return false;
+        }
+        let current = self.take_visit();
+        if let Some(visit) = self.history.forward(current) {
+            self.restore_visit(visit);
+        }
+        true
+    }
+
+    /// Move the open article out of the tab, to be put in the history.
+    fn take_visit(&mut self) -> Visit {
+        Visit {
+            title: std::mem::take(&mut self.title),
+            page: std::mem::take(&mut self.page),
+            toc: std::mem::take(&mut self.toc.items),
+            langlinks: std::mem::take(&mut self.langlinks),
+            lang: self.lang.clone(),
+            url: self.url.clone(),
+            scroll: self.scroll,
+        }
+    }
+
+    fn restore_visit(&mut self, visit: Visit) {
+        self.title = visit.title;
+        self.page = visit.page;
+        self.toc = StatefulList::with_items(visit.toc);
+        self.langlinks = visit.langlinks;
+        self.lang = visit.lang;
+        self.url = visit.url;
+        self.scroll = visit.scroll;
+        self.link = None;
+        self.rendered = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wikitext;
+
+    #[test]
+    fn tab_sections_by_anchor() {
+        let mut tab = Tab::new(0, "en", String::new());
+        tab.page = wikitext::parse("== One ==\na\n== Unlisted ==\nb\n== Two ==\nc");
+        // The API lists a transcluded section the parser does not render
+        tab.toc = StatefulList::with_items(vec![
+            (" 1 One".to_owned(), "One".to_owned()),
+            (" 2 Included".to_owned(), "Included".to_owned()),
+            (" 3 Two".to_owned(), "Two".to_owned()),
+        ]);
+        let rendered = tab.rendered(80, &Theme::default());
+        let two = tab.section_row(&rendered, 2).unwrap();
+        assert_eq!(rendered.headings[2], ("Two".to_owned(), two));
+        assert_eq!(tab.section_row(&rendered, 1), None);
+        assert_eq!(tab.section_at(&rendered, two), Some(2));
+        assert_eq!(tab.section_at(&rendered, two - 1), Some(0));
+    }
+}
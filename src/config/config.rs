@@ -0,0 +1,139 @@
+use crate::request::DEFAULT_LANG;
+use serde::Deserialize;
+use std::error::Error;
+use std::path::PathBuf;
+use std::{env, fs};
+
+const USAGE: &str = "Usage: wiki [-l|--lang <code>]";
+
+/// User settings, read from `$XDG_CONFIG_HOME/wikipedia-tui/config.toml`
+/// (`~/.config/wikipedia-tui/config.toml` by default) and overridden by
+/// command line flags.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Wikipedia edition, e.g. `en`, `de` or `ja`
+    pub lang: String,
+    /// Where `:export` writes articles, `exports` next to this file if unset
+    pub export_dir: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            lang: DEFAULT_LANG.to_owned(),
+            export_dir: None,
+        }
+    }
+}
+
+impl Config {
+    /// Load the config file if there is one, then apply the command line.
+    pub fn load() -> Result<Self, Box<dyn Error>> {
+        let config = match Self::dir().map(|dir| dir.join("config.toml")) {
+            Some(path) if path.exists() => {
+                let text = fs::read_to_string(&path)?;
+                Self::from_toml(&text).map_err(|err| format!("{}: {}", path.display(), err))?
+            }
+            _ => Self::default(),
+        };
+        config.with_args(env::args().skip(1))
+    }
+
+    /// Directory holding the config file and any other user data.
+    pub fn dir() -> Option<PathBuf> {
+        env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .map(|dir| dir.join("wikipedia-tui"))
+    }
+
+    /// Directory `:export` writes articles to.
+    pub fn export_dir(&self) -> Option<PathBuf> {
+        self.export_dir
+            .clone()
+            .or_else(|| Self::dir().map(|dir| dir.join("exports")))
+    }
+
+    fn from_toml(text: &str) -> Result<Self, Box<dyn Error>> {
+        let config: Self = toml::from_str(text)?;
+        validate_lang(&config.lang)?;
+        Ok(config)
+    }
+
+    fn with_args(mut self, args: impl Iterator<Item = String>) -> Result<Self, Box<dyn Error>> {
+        let mut args = args;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-l" | "--lang" => {
+                    let lang = args.next().ok_or(USAGE)?;
+                    validate_lang(&lang)?;
+                    self.lang = lang;
+                }
+                "-h" | "--help" => return Err(USAGE.into()),
+                _ => return Err(format!("Unknown argument '{}'\n{}", arg, USAGE).into()),
+            }
+        }
+        Ok(self)
+    }
+}
+
+/// Language codes end up in the host name, e.g. `de` or `zh-yue`.
+pub fn validate_lang(lang: &str) -> Result<(), String> {
+    let valid = lang.starts_with(|c: char| c.is_ascii_lowercase())
+        && lang
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid language code '{}'", lang))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> impl Iterator<Item = String> {
+        args.iter()
+            .map(|arg| arg.to_string())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    #[test]
+    fn config_from_toml() {
+        assert_eq!(
+            Config::from_toml("lang = \"de\"").unwrap(),
+            Config {
+                lang: "de".to_owned(),
+                export_dir: None,
+            }
+        );
+        assert_eq!(
+            Config::from_toml("export_dir = \"/tmp/wiki\"")
+                .unwrap()
+                .export_dir(),
+            Some(PathBuf::from("/tmp/wiki"))
+        );
+        assert_eq!(Config::from_toml("").unwrap(), Config::default());
+        assert!(Config::from_toml("lang = \"en.evil.com/\"").is_err());
+        assert!(Config::from_toml("colour = \"red\"").is_err());
+    }
+
+    #[test]
+    fn config_with_args() {
+        let config = Config::default()
+            .with_args(args(&["--lang", "ja"]))
+            .unwrap();
+        assert_eq!(config.lang, "ja");
+        let config = Config::default()
+            .with_args(args(&["-l", "zh-yue"]))
+            .unwrap();
+        assert_eq!(config.lang, "zh-yue");
+        assert!(Config::default().with_args(args(&["--lang"])).is_err());
+        assert!(Config::default().with_args(args(&["--colour"])).is_err());
+    }
+}
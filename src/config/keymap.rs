@@ -0,0 +1,452 @@
+use crate::app::Mode;
+use crate::util::Key;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::str::FromStr;
+
+/// Bindings the presets and the keymap file are applied on top of
+const DEFAULT: &str = r#"
+[global]
+esc = "escape"
+enter = "enter"
+up = "up"
+down = "down"
+left = "left"
+right = "right"
+pageup = "page-up"
+pagedown = "page-down"
+home = "home"
+end = "end"
+tab = "focus-next"
+backtab = "focus-previous"
+backspace = "backspace"
+delete = "delete"
+ctrl-left = "word-left"
+ctrl-right = "word-right"
+alt-b = "word-left"
+alt-f = "word-right"
+ctrl-c = "quit"
+ctrl-l = "language"
+alt-c = "find-case"
+alt-r = "find-regex"
+":" = "command"
+
+[search]
+ctrl-a = "line-start"
+ctrl-e = "line-end"
+ctrl-w = "delete-word"
+ctrl-u = "delete-to-start"
+ctrl-r = "reverse-search"
+ctrl-t = "full-text"
+
+[browse]
+q = "quit"
+Q = "quit"
+
+[read]
+q = "quit"
+Q = "quit"
+ctrl-d = "half-page-down"
+ctrl-u = "half-page-up"
+"<" = "previous-link"
+">" = "next-link"
+o = "open-in-new-tab"
+"/" = "find"
+n = "find-next"
+N = "find-previous"
+b = "back"
+f = "forward"
+c = "contents"
+l = "language-links"
+t = "new-tab"
+w = "close-tab"
+"[" = "previous-tab"
+"]" = "next-tab"
+1 = "tab-1"
+2 = "tab-2"
+3 = "tab-3"
+4 = "tab-4"
+5 = "tab-5"
+6 = "tab-6"
+7 = "tab-7"
+8 = "tab-8"
+9 = "tab-9"
+
+[toc]
+q = "quit"
+Q = "quit"
+
+[url]
+q = "quit"
+Q = "quit"
+"#;
+
+const VIM: &str = r#"
+[browse]
+j = "down"
+k = "up"
+g = "home"
+G = "end"
+"/" = "focus-search"
+
+[read]
+j = "down"
+k = "up"
+g = "home"
+G = "end"
+ctrl-f = "page-down"
+ctrl-b = "page-up"
+
+[toc]
+j = "down"
+k = "up"
+g = "home"
+G = "end"
+"#;
+
+const EMACS: &str = r#"
+[global]
+ctrl-g = "escape"
+ctrl-n = "down"
+ctrl-p = "up"
+ctrl-v = "page-down"
+alt-v = "page-up"
+"alt-<" = "home"
+"alt->" = "end"
+
+[search]
+ctrl-b = "left"
+ctrl-f = "right"
+ctrl-d = "delete"
+
+[read]
+ctrl-s = "find"
+"#;
+
+/// Something a key can be bound to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Quit,
+    Escape,
+    Enter,
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    HalfPageUp,
+    HalfPageDown,
+    FocusNext,
+    FocusPrevious,
+    FocusSearch,
+    Backspace,
+    Delete,
+    WordLeft,
+    WordRight,
+    LineStart,
+    LineEnd,
+    DeleteWord,
+    DeleteToStart,
+    ReverseSearch,
+    FullText,
+    Language,
+    LanguageLinks,
+    Contents,
+    PreviousLink,
+    NextLink,
+    OpenInNewTab,
+    Find,
+    FindNext,
+    FindPrevious,
+    FindCase,
+    FindRegex,
+    Back,
+    Forward,
+    NewTab,
+    CloseTab,
+    PreviousTab,
+    NextTab,
+    /// Switch to the nth tab, counting from 1
+    Tab(usize),
+    /// Open the `:` command line
+    Command,
+}
+
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let action = match s {
+            "quit" => Action::Quit,
+            "escape" => Action::Escape,
+            "enter" => Action::Enter,
+            "up" => Action::Up,
+            "down" => Action::Down,
+            "left" => Action::Left,
+            "right" => Action::Right,
+            "page-up" => Action::PageUp,
+            "page-down" => Action::PageDown,
+            "home" => Action::Home,
+            "end" => Action::End,
+            "half-page-up" => Action::HalfPageUp,
+            "half-page-down" => Action::HalfPageDown,
+            "focus-next" => Action::FocusNext,
+            "focus-previous" => Action::FocusPrevious,
+            "focus-search" => Action::FocusSearch,
+            "backspace" => Action::Backspace,
+            "delete" => Action::Delete,
+            "word-left" => Action::WordLeft,
+            "word-right" => Action::WordRight,
+            "line-start" => Action::LineStart,
+            "line-end" => Action::LineEnd,
+            "delete-word" => Action::DeleteWord,
+            "delete-to-start" => Action::DeleteToStart,
+            "reverse-search" => Action::ReverseSearch,
+            "full-text" => Action::FullText,
+            "language" => Action::Language,
+            "language-links" => Action::LanguageLinks,
+            "contents" => Action::Contents,
+            "previous-link" => Action::PreviousLink,
+            "next-link" => Action::NextLink,
+            "open-in-new-tab" => Action::OpenInNewTab,
+            "find" => Action::Find,
+            "find-next" => Action::FindNext,
+            "find-previous" => Action::FindPrevious,
+            "find-case" => Action::FindCase,
+            "find-regex" => Action::FindRegex,
+            "back" => Action::Back,
+            "forward" => Action::Forward,
+            "new-tab" => Action::NewTab,
+            "close-tab" => Action::CloseTab,
+            "previous-tab" => Action::PreviousTab,
+            "next-tab" => Action::NextTab,
+            "command" => Action::Command,
+            _ => match s.strip_prefix("tab-").and_then(|n| n.parse().ok()) {
+                Some(n) if n > 0 => Action::Tab(n),
+                _ => return Err(format!("Unknown action '{}'", s)),
+            },
+        };
+        Ok(action)
+    }
+}
+
+/// Key bindings per mode, read from `keymap.toml` next to the config file.
+///
+/// ```toml
+/// preset = "vim"
+///
+/// [read]
+/// space = "page-down"
+/// b = "none"
+/// ```
+///
+/// Bindings of the focused mode come first, then those of `[global]`.
+/// Characters are typed in Search and Command mode, so `[search]` and
+/// `[command]` cannot bind them and `[global]` ones do nothing there.
+#[derive(Debug)]
+pub struct Keymap {
+    global: HashMap<Key, Action>,
+    modes: HashMap<Mode, HashMap<Key, Action>>,
+}
+
+impl Keymap {
+    /// Load the keymap file if there is one.
+    pub fn load() -> Result<Self, Box<dyn Error>> {
+        match super::Config::dir().map(|dir| dir.join("keymap.toml")) {
+            Some(path) if path.exists() => {
+                let text = fs::read_to_string(&path)?;
+                Ok(Self::from_toml(&text).map_err(|err| format!("{}: {}", path.display(), err))?)
+            }
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// The action bound to `key` when `mode` has focus.
+    pub fn action(&self, mode: Mode, key: Key) -> Option<Action> {
+        self.modes
+            .get(&mode)
+            .and_then(|bindings| bindings.get(&key))
+            .or_else(|| self.global.get(&key))
+            .copied()
+    }
+
+    /// The keys bound to `action` when `mode` has focus, in a stable order.
+    pub fn keys(&self, mode: Mode, action: Action) -> Vec<Key> {
+        let bindings = self.modes.get(&mode).into_iter().flatten();
+        let mut keys: Vec<Key> = bindings
+            .chain(&self.global)
+            .map(|(key, _)| *key)
+            .filter(|key| !(typed(mode) && matches!(key, Key::Char(_))))
+            // A binding of the mode hides the global one of the same key
+            .filter(|key| self.action(mode, *key) == Some(action))
+            .collect();
+        keys.sort();
+        keys.dedup();
+        keys
+    }
+
+    fn from_toml(text: &str) -> Result<Self, Box<dyn Error>> {
+        let mut value: toml::value::Table = toml::from_str(text)?;
+        let preset = match value.remove("preset") {
+            Some(toml::Value::String(preset)) => match preset.as_str() {
+                "default" => "",
+                "vim" => VIM,
+                "emacs" => EMACS,
+                _ => return Err(format!("Unknown preset '{}'", preset).into()),
+            },
+            Some(_) => return Err("preset should be a string".into()),
+            None => "",
+        };
+        let mut keymap = Self::default();
+        keymap.bind(toml::from_str(preset)?)?;
+        keymap.bind(value)?;
+        Ok(keymap)
+    }
+
+    /// Add the bindings of every table in `tables`, action `none` unbinds.
+    fn bind(&mut self, tables: toml::value::Table) -> Result<(), Box<dyn Error>> {
+        for (section, bindings) in tables {
+            let mode = match section.as_str() {
+                "global" => None,
+                "search" => Some(Mode::Search),
+                "browse" => Some(Mode::Browse),
+                "read" => Some(Mode::Read),
+                "toc" => Some(Mode::Toc),
+                "url" => Some(Mode::Url),
+                "command" => Some(Mode::Command),
+                _ => return Err(format!("Unknown section '{}'", section).into()),
+            };
+            let table = match mode {
+                Some(mode) => self.modes.entry(mode).or_default(),
+                None => &mut self.global,
+            };
+            let bindings = match bindings {
+                toml::Value::Table(bindings) => bindings,
+                _ => return Err(format!("[{}] should be a table", section).into()),
+            };
+            for (name, action) in bindings {
+                let key = name.parse::<Key>()?;
+                if mode.is_some_and(typed) && matches!(key, Key::Char(_)) {
+                    let err = format!("[{}] cannot bind '{}', it is typed there", section, name);
+                    return Err(err.into());
+                }
+                match action.as_str() {
+                    Some("none") => {
+                        table.remove(&key);
+                    }
+                    Some(action) => {
+                        table.insert(key, action.parse()?);
+                    }
+                    None => return Err(format!("Action for '{}' should be a string", name).into()),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether characters typed in `mode` go to a text field
+fn typed(mode: Mode) -> bool {
+    matches!(mode, Mode::Search | Mode::Command)
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let mut keymap = Self {
+            global: HashMap::new(),
+            modes: HashMap::new(),
+        };
+        keymap
+            .bind(toml::from_str(DEFAULT).unwrap())
+            .expect("invalid default keymap");
+        keymap
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keymap_default() {
+        let keymap = Keymap::default();
+        assert_eq!(
+            keymap.action(Mode::Read, Key::Char('q')),
+            Some(Action::Quit)
+        );
+        assert_eq!(keymap.action(Mode::Search, Key::Char('q')), None);
+        assert_eq!(
+            keymap.action(Mode::Search, Key::Ctrl('c')),
+            Some(Action::Quit)
+        );
+        assert_eq!(
+            keymap.action(Mode::Read, Key::Char('3')),
+            Some(Action::Tab(3))
+        );
+        assert_eq!(
+            keymap.action(Mode::Search, Key::Ctrl('u')),
+            Some(Action::DeleteToStart)
+        );
+        assert_eq!(
+            keymap.action(Mode::Read, Key::Ctrl('u')),
+            Some(Action::HalfPageUp)
+        );
+    }
+
+    #[test]
+    fn keymap_from_toml() {
+        let keymap = Keymap::from_toml(
+            "preset = \"vim\"\n[read]\nspace = \"page-down\"\nj = \"none\"\n[global]\nf5 = \"focus-search\"",
+        )
+        .unwrap();
+        assert_eq!(keymap.action(Mode::Toc, Key::Char('j')), Some(Action::Down));
+        assert_eq!(keymap.action(Mode::Read, Key::Char('j')), None);
+        assert_eq!(
+            keymap.action(Mode::Read, Key::Char(' ')),
+            Some(Action::PageDown)
+        );
+        assert_eq!(
+            keymap.action(Mode::Url, Key::F(5)),
+            Some(Action::FocusSearch)
+        );
+        let keymap = Keymap::from_toml("preset = \"emacs\"").unwrap();
+        assert_eq!(
+            keymap.action(Mode::Browse, Key::Ctrl('n')),
+            Some(Action::Down)
+        );
+        assert!(Keymap::from_toml("preset = \"nano\"").is_err());
+        assert!(Keymap::from_toml("[read]\nq = \"explode\"").is_err());
+        assert!(Keymap::from_toml("[read]\nctrl-enter-x = \"quit\"").is_err());
+        assert!(Keymap::from_toml("[editor]\nq = \"quit\"").is_err());
+        assert!(Keymap::from_toml("[search]\n\"?\" = \"full-text\"").is_err());
+        assert!(Keymap::from_toml("[command]\nspace = \"enter\"").is_err());
+    }
+
+    #[test]
+    fn keymap_keys() {
+        let keymap = Keymap::default();
+        assert_eq!(
+            keymap.keys(Mode::Read, Action::Quit),
+            vec![Key::Ctrl('c'), Key::Char('Q'), Key::Char('q')]
+        );
+        assert_eq!(
+            keymap.keys(Mode::Search, Action::Quit),
+            vec![Key::Ctrl('c')]
+        );
+        assert_eq!(keymap.keys(Mode::Read, Action::DeleteToStart), vec![]);
+        assert_eq!(keymap.keys(Mode::Search, Action::Command), vec![]);
+        let keymap = Keymap::from_toml("preset = \"vim\"\n[read]\nq = \"none\"").unwrap();
+        assert_eq!(
+            keymap.keys(Mode::Read, Action::Down),
+            vec![Key::Down, Key::Char('j')]
+        );
+        assert_eq!(
+            keymap.keys(Mode::Read, Action::Quit),
+            vec![Key::Ctrl('c'), Key::Char('Q')]
+        );
+    }
+}
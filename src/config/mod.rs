@@ -0,0 +1,5 @@
+#[allow(clippy::module_inception)]
+mod config;
+mod keymap;
+pub use config::{validate_lang, Config};
+pub use keymap::{Action, Keymap};
@@ -1,9 +1,13 @@
 mod app;
+mod config;
 mod request;
 mod ui;
 mod util;
+mod wikitext;
 
 use crate::app::{App, Mode};
+use crate::config::{Action, Config, Keymap};
+use crate::request::Worker;
 use crate::util::{Event, Events, Key};
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
@@ -14,6 +18,14 @@ use std::io::Write;
 use tui::{backend::CrosstermBackend, Terminal};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let (config, keymap) = match Config::load().and_then(|config| Ok((config, Keymap::load()?))) {
+        Ok(loaded) => loaded,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(2);
+        }
+    };
+
     // Initialise terminal
     enable_raw_mode()?;
     let mut stdout = std::io::stdout();
@@ -25,36 +37,42 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let events = Events::new();
 
     // Initialise app
-    let mut app = App::new("wikipedia-tui");
+    let mut app = App::new(config, Worker::new(events.sender()), Config::dir());
 
     loop {
-        terminal.draw(|mut f| ui::draw(&mut f, &mut app))?;
+        terminal.draw(|mut f| ui::draw(&mut f, &mut app, &keymap))?;
 
         // Handle mode
-        match app.mode {
-            Mode::Search => {
+        match app.mode() {
+            Mode::Search if app.picker.is_none() => {
                 terminal.show_cursor()?;
                 terminal.set_cursor(app.search_cursor_x, app.search_cursor_y)?;
             }
+            Mode::Search => terminal.hide_cursor()?,
             Mode::Browse => terminal.hide_cursor()?,
             Mode::Read => terminal.hide_cursor()?,
+            Mode::Toc => terminal.hide_cursor()?,
+            Mode::Url => terminal.hide_cursor()?,
+            Mode::Command => {
+                terminal.show_cursor()?;
+                terminal.set_cursor(app.command_cursor_x, app.command_cursor_y)?;
+            }
         }
 
-        // Handle key event received
-        if let Event::Input(key) = events.next()? {
-            match key {
-                Key::Char('q') | Key::Char('Q') => break,
-                Key::Esc => app.on_escape(),
-                Key::Enter => app.on_enter(),
-                Key::Up => app.on_up(),
-                Key::Down => app.on_down(),
-                Key::Left => app.on_left(),
-                Key::Right => app.on_right(),
-                Key::Char(key) => app.on_key(key),
-                Key::Backspace => app.on_backspace(),
-                Key::Alt(_) => {}
-                _ => {}
-            }
+        // Handle event received
+        match events.next()? {
+            Event::Input(Key::Char(c)) if app.is_typing() => app.on_key(c),
+            Event::Input(key) => match keymap.action(app.mode(), key) {
+                Some(Action::Quit) if app.error.is_none() => break,
+                Some(action) => app.on_action(action),
+                None => {}
+            },
+            Event::Mouse(event) => app.on_mouse(event),
+            Event::Tick => app.on_tick(),
+            Event::Response(id, res) => app.on_response(id, res),
+        }
+        if app.quit {
+            break;
         }
     }
 
@@ -0,0 +1,48 @@
+use reqwest::StatusCode;
+use std::fmt;
+
+/// Everything that can go wrong while talking to the MediaWiki API.
+#[derive(Debug)]
+pub enum Error {
+    /// Connection, DNS or TLS failure
+    Transport(reqwest::Error),
+    /// Non-success HTTP status
+    Status(StatusCode),
+    /// `error` block returned by the API
+    Api { code: String, info: String },
+    /// Unexpected response body
+    Parse(serde_json::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Transport(err) => write!(f, "Network error: {}", err),
+            Error::Status(status) => write!(f, "Server responded with {}", status),
+            Error::Api { code, info } => write!(f, "{} ({})", info, code),
+            Error::Parse(err) => write!(f, "Unexpected response: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Transport(err) => Some(err),
+            Error::Parse(err) => Some(err),
+            Error::Status(_) | Error::Api { .. } => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(err: reqwest::Error) -> Self {
+        Error::Transport(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Parse(err)
+    }
+}
@@ -1,5 +1,12 @@
+mod error;
+#[allow(clippy::module_inception)]
 mod request;
+mod response;
 mod url;
+mod worker;
 
+pub use error::Error;
 pub use request::Request;
-pub use url::Endpoint;
+pub use response::{LangLink, SearchResult};
+pub use url::{Endpoint, DEFAULT_LANG};
+pub use worker::Worker;
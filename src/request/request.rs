@@ -1,14 +1,33 @@
-use crate::request::url::{Endpoint, PageParse, PrefixSearch};
-use regex::Regex;
-use reqwest::blocking::Client;
+use crate::request::error::Error;
+use crate::request::response::{LangLink, Response, SearchResult};
+use crate::request::url::{Action, ApiQuery, Endpoint, DEFAULT_LANG};
+use crate::wikitext::{self, Block, Document, Inline};
+use reqwest::Client;
 
 pub struct Request {
     client: Client,
     url: String,
     endpoint: Endpoint,
-    pub page_list: Vec<String>,
-    pub toc_list: Vec<String>,
-    pub page: Vec<String>,
+    pub lang: String,
+    /// Title of the parsed page, after following redirects
+    pub title: String,
+    /// Title the page was reached from, if it was a redirect
+    pub redirected_from: Option<String>,
+    /// Section a redirect points at
+    pub fragment: Option<String>,
+    pub page_list: Vec<SearchResult>,
+    /// Offset of the next batch of search results, if there are more
+    pub next_offset: Option<u16>,
+    /// Number of matches, when the endpoint reports it
+    pub total: Option<u64>,
+    /// `(label, anchor)` of each entry of the Table of Content
+    pub toc_list: Vec<(String, String)>,
+    pub page: Document,
+    pub langlinks: Vec<LangLink>,
+    /// `(title, description)` of each entry, if the page is a disambiguation
+    /// page
+    pub disambiguation: Vec<(String, String)>,
+    pub warnings: Vec<String>,
 }
 
 impl Default for Request {
@@ -17,84 +36,149 @@ impl Default for Request {
             client: Client::new(),
             url: String::new(),
             endpoint: Endpoint::PrefixSearch,
+            lang: DEFAULT_LANG.to_owned(),
+            title: String::new(),
+            redirected_from: None,
+            fragment: None,
             page_list: Vec::new(),
+            next_offset: None,
+            total: None,
             toc_list: Vec::new(),
-            page: Vec::new(),
+            page: Document::default(),
+            langlinks: Vec::new(),
+            disambiguation: Vec::new(),
+            warnings: Vec::new(),
         }
     }
 }
 
+/// Search results fetched per request
+const SEARCH_LIMIT: u16 = 30;
+
 impl<'a> Request {
-    pub fn new(search: &'a str, url: Endpoint) -> Self {
-        let mut req = Self::default();
+    pub fn new(search: &'a str, lang: &'a str, url: Endpoint) -> Self {
+        Self::with_offset(search, lang, 0, url)
+    }
+
+    /// Like `new`, but search results start at `offset`.
+    pub fn with_offset(search: &'a str, lang: &'a str, offset: u16, url: Endpoint) -> Self {
+        let mut req = Self {
+            lang: lang.to_owned(),
+            ..Self::default()
+        };
         match url {
             Endpoint::PrefixSearch => {
                 req.endpoint = Endpoint::PrefixSearch;
-                req.url = PrefixSearch::new()
-                    .search(search)
-                    .limit(30)
-                    .offset(0)
+                req.url = ApiQuery::new(Action::Query)
+                    .lang(lang)
+                    .list("prefixsearch")
+                    .param("pssearch", search)
+                    .param("pslimit", SEARCH_LIMIT)
+                    .param("psoffset", offset)
+                    .parse();
+            }
+            Endpoint::FullTextSearch => {
+                req.endpoint = Endpoint::FullTextSearch;
+                req.url = ApiQuery::new(Action::Query)
+                    .lang(lang)
+                    .list("search")
+                    .param("srsearch", search)
+                    .param("srlimit", SEARCH_LIMIT)
+                    .param("sroffset", offset)
+                    .param("srprop", "snippet|wordcount|timestamp")
                     .parse();
             }
             Endpoint::PageParse => {
                 req.endpoint = Endpoint::PageParse;
-                req.url = PageParse::new().page(search).parse();
+                req.url = ApiQuery::new(Action::Parse)
+                    .lang(lang)
+                    .prop("wikitext")
+                    .prop("sections")
+                    .prop("langlinks")
+                    .prop("properties")
+                    .param("page", search)
+                    .param("redirects", 1)
+                    .parse();
+            }
+            Endpoint::Random => {
+                req.endpoint = Endpoint::Random;
+                req.url = ApiQuery::new(Action::Query)
+                    .lang(lang)
+                    .list("random")
+                    .param("rnnamespace", 0)
+                    .param("rnlimit", 1)
+                    .parse();
             }
         }
         req
     }
 
-    pub fn fetch(mut self) -> Self {
-        let res = self.client.get(&self.url).send().unwrap().text().unwrap();
+    pub async fn fetch(mut self) -> Result<Self, Error> {
+        let res = self.client.get(&self.url).send().await?;
+        if !res.status().is_success() {
+            return Err(Error::Status(res.status()));
+        }
+        let res: Response = serde_json::from_str(&res.text().await?)?;
+        if let Some(error) = res.error {
+            return Err(Error::Api {
+                code: error.code,
+                info: error.info,
+            });
+        }
+        self.warnings = res
+            .warnings
+            .into_iter()
+            .map(|(module, warning)| format!("{}: {}", module, warning.warnings))
+            .collect();
         match self.endpoint {
             Endpoint::PrefixSearch => {
-                let re = Regex::new(r#""title":"(.+?)""#).unwrap(); //TODO: lazy_static!
-                for each in re.captures_iter(&res) {
-                    if let Some(match_) = each.get(1) {
-                        self.page_list.push(match_.as_str().to_owned());
-                    }
+                if let Some(query) = res.query {
+                    self.page_list = query.prefixsearch;
+                }
+                self.next_offset = res.continuation.and_then(|c| c.psoffset);
+            }
+            Endpoint::FullTextSearch => {
+                if let Some(query) = res.query {
+                    self.page_list = query.search;
+                    self.total = query.searchinfo.map(|info| info.totalhits);
+                }
+                self.next_offset = res.continuation.and_then(|c| c.sroffset);
+            }
+            Endpoint::Random => {
+                if let Some(query) = res.query {
+                    self.page_list = query.random;
                 }
             }
             Endpoint::PageParse => {
-                let re = Regex::new(r#""sections":(.+),"wikitext":(.+)"#).unwrap(); //TODO: lazy_static!
-                for each in re.captures_iter(&res) {
+                if let Some(parse) = res.parse {
                     // Parse sections (toc)
-                    if let Some(match_) = each.get(1) {
-                        let re = Regex::new(r#""line":"(.+?)".*?"number":"(.+?)""#).unwrap(); //TODO: lazy_static!
-                        for each in re.captures_iter(&match_.as_str()) {
-                            if let Some(match_1) = each.get(1) {
-                                if let Some(match_2) = each.get(2) {
-                                    let txt = match_1.as_str();
-                                    let num = match_2.as_str();
-                                    let indent = "  ".repeat(num.matches(".").count());
-                                    self.toc_list.push(format!("{} {} {}", indent, num, txt));
-                                }
-                            }
-                        }
+                    for section in parse.sections {
+                        let indent = "  ".repeat(section.toclevel.saturating_sub(1) as usize);
+                        self.toc_list.push((
+                            format!("{} {} {}", indent, section.number, section.line),
+                            section.anchor,
+                        ));
                     }
                     // Parse wikitext
-                    if let Some(match_) = each.get(2) {
-                        let text = match_.as_str();
-                        let re = Regex::new(
-                            r#"\{\{.+?\}\}|<ref\\s*>.+?</ref>|<ref .+?>|\[\[File.+?\]\]|<.ref>"#,
-                        )
-                        .unwrap(); //TODO: lazy_static!
-                        let text = re.replace_all(text, "");
-                        let re = Regex::new(r#"\\n\\n\\n"#).unwrap(); //TODO: lazy_static!
-                        let text = re.replace_all(&text, "");
-                        let re = Regex::new(r#"\\n\\n\\n"#).unwrap(); //TODO: lazy_static!
-                        let text = re.replace_all(&text, "");
-                        for each in text.split("\\n") {
-                            if each == "\"" {
-                                continue;
-                            }
-                            self.page.push(format!("{}\n", each));
-                        }
+                    self.page = wikitext::parse(&parse.wikitext);
+                    self.langlinks = parse.langlinks;
+                    self.title = parse.title;
+                    if parse.properties.contains_key("disambiguation") {
+                        self.disambiguation = self.page.link_list();
+                    }
+                    if let Some(redirect) = parse.redirects.into_iter().next() {
+                        let notice = format!("(Redirected from {})", redirect.from);
+                        self.page.blocks.insert(
+                            0,
+                            Block::Paragraph(vec![Inline::Italic(vec![Inline::Text(notice)])]),
+                        );
+                        self.redirected_from = Some(redirect.from);
+                        self.fragment = redirect.tofragment;
                     }
                 }
             }
         }
-        self
+        Ok(self)
     }
 }
 
@@ -102,15 +186,26 @@ impl<'a> Request {
 mod tests {
     use super::*;
 
-    #[test]
-    fn request_fetch_prefix_search() {
-        let res = Request::new("rust", Endpoint::PrefixSearch).fetch();
-        assert_eq!(res.page_list, vec!["Rust".to_owned()]);
+    #[tokio::test]
+    async fn request_fetch_prefix_search() {
+        let res = Request::new("rust", DEFAULT_LANG, Endpoint::PrefixSearch)
+            .fetch()
+            .await
+            .unwrap();
+        let titles = res
+            .page_list
+            .into_iter()
+            .map(|result| result.title)
+            .collect::<Vec<_>>();
+        assert_eq!(titles, vec!["Rust".to_owned()]);
     }
 
-    #[test]
-    fn request_fetch_parse_page() {
-        let res = Request::new("rust", Endpoint::PageParse).fetch();
-        // assert_eq!(res.page_list, vec!["Rust".to_owned()]);
+    #[tokio::test]
+    async fn request_fetch_parse_page() {
+        let res = Request::new("rust", DEFAULT_LANG, Endpoint::PageParse)
+            .fetch()
+            .await
+            .unwrap();
+        assert!(!res.page.blocks.is_empty());
     }
 }
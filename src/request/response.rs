@@ -0,0 +1,246 @@
+use crate::wikitext::decode_entities;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+lazy_static! {
+    static ref SEARCH_MATCH: Regex =
+        Regex::new(r#"<span class="searchmatch">(.*?)</span>"#).unwrap();
+    static ref TAG: Regex = Regex::new(r"<[^>]*>").unwrap();
+}
+
+/// Top level MediaWiki API response (`format=json&formatversion=2`). Every
+/// endpoint shares the `error`/`warnings` blocks, the payload lives under the
+/// key of the module that produced it.
+#[derive(Debug, Default, Deserialize)]
+pub struct Response {
+    pub error: Option<ApiError>,
+    #[serde(default)]
+    pub warnings: HashMap<String, Warning>,
+    pub query: Option<Query>,
+    pub parse: Option<Parse>,
+    /// Parameters to fetch the next batch of results, if there is one
+    #[serde(rename = "continue")]
+    pub continuation: Option<Continue>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApiError {
+    pub code: String,
+    pub info: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Warning {
+    #[serde(alias = "*")]
+    pub warnings: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Continue {
+    pub psoffset: Option<u16>,
+    pub sroffset: Option<u16>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Query {
+    #[serde(default)]
+    pub prefixsearch: Vec<SearchResult>,
+    #[serde(default)]
+    pub search: Vec<SearchResult>,
+    #[serde(default)]
+    pub random: Vec<SearchResult>,
+    /// Only given by full-text search
+    pub searchinfo: Option<SearchInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchInfo {
+    pub totalhits: u64,
+}
+
+/// A page found by prefix or full-text search. Only full-text search fills
+/// in the snippet, word count and timestamp.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SearchResult {
+    pub title: String,
+    /// Html excerpt, matched terms wrapped in `<span class="searchmatch">`
+    #[serde(default)]
+    pub snippet: String,
+    pub wordcount: Option<u64>,
+    /// Last edit, e.g. `2020-05-01T12:34:56Z`
+    pub timestamp: Option<String>,
+}
+
+impl SearchResult {
+    /// The snippet as plain text, split into `(text, is_match)` parts.
+    pub fn snippet_parts(&self) -> Vec<(String, bool)> {
+        let mut parts = Vec::new();
+        let mut push = |html: &str, is_match| {
+            let text = decode_entities(&TAG.replace_all(html, "")).replace('\n', " ");
+            if !text.is_empty() {
+                parts.push((text, is_match));
+            }
+        };
+        let mut last = 0;
+        for caps in SEARCH_MATCH.captures_iter(&self.snippet) {
+            let whole = caps.get(0).unwrap();
+            push(&self.snippet[last..whole.start()], false);
+            push(&caps[1], true);
+            last = whole.end();
+        }
+        push(&self.snippet[last..], false);
+        parts
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Parse {
+    /// Title of the parsed page, after following redirects
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub redirects: Vec<Redirect>,
+    #[serde(default)]
+    pub sections: Vec<Section>,
+    #[serde(default)]
+    pub wikitext: String,
+    #[serde(default)]
+    pub langlinks: Vec<LangLink>,
+    /// Page properties, e.g. `disambiguation` for disambiguation pages
+    #[serde(default)]
+    pub properties: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Redirect {
+    pub from: String,
+    /// Section the redirect points at, e.g. `History`
+    pub tofragment: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Section {
+    pub toclevel: u8,
+    pub line: String,
+    pub number: String,
+    /// Fragment of links to the section, e.g. `History_2`
+    #[serde(default)]
+    pub anchor: String,
+}
+
+/// The same article in another language edition
+#[derive(Debug, Clone, Deserialize)]
+pub struct LangLink {
+    pub lang: String,
+    pub title: String,
+    /// Name of the language in that language, e.g. `Deutsch`
+    #[serde(default)]
+    pub autonym: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn response_prefix_search() {
+        let res: Response = serde_json::from_str(
+            r#"{"batchcomplete":true,"continue":{"psoffset":30,"continue":"-||"},"query":{"prefixsearch":[
+                {"ns":0,"title":"Café","pageid":1},
+                {"ns":0,"title":"\"Weird Al\" Yankovic","pageid":2}
+            ]}}"#,
+        )
+        .unwrap();
+        assert_eq!(res.continuation.unwrap().psoffset, Some(30));
+        let titles = res
+            .query
+            .unwrap()
+            .prefixsearch
+            .into_iter()
+            .map(|result| result.title)
+            .collect::<Vec<_>>();
+        assert_eq!(titles, vec!["Café", "\"Weird Al\" Yankovic"]);
+    }
+
+    #[test]
+    fn response_full_text_search() {
+        let res: Response = serde_json::from_str(
+            r#"{"query":{"searchinfo":{"totalhits":2},"search":[{"ns":0,"title":"Speed of light",
+                "pageid":1,"wordcount":12345,"timestamp":"2020-05-01T12:34:56Z",
+                "snippet":"The <span class=\"searchmatch\">speed</span> of &quot;light&quot; <b>in</b> vacuum"}]}}"#,
+        )
+        .unwrap();
+        assert!(res.continuation.is_none());
+        let query = res.query.unwrap();
+        assert_eq!(query.searchinfo.unwrap().totalhits, 2);
+        let result = &query.search[0];
+        assert_eq!(result.wordcount, Some(12345));
+        assert_eq!(
+            result.snippet_parts(),
+            vec![
+                ("The ".to_owned(), false),
+                ("speed".to_owned(), true),
+                (" of \"light\" in vacuum".to_owned(), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn response_parse() {
+        let res: Response = serde_json::from_str(
+            r#"{"parse":{"title":"Rust","pageid":7,"sections":[
+                {"toclevel":1,"level":"2","line":"Chemistry","number":"1","index":"1","anchor":"Chemistry"},
+                {"toclevel":2,"level":"3","line":"Iron","number":"1.1","index":"2","anchor":"Iron"}
+            ],"wikitext":"'''Rust''' is an \"iron oxide\".\n== Chemistry ==","langlinks":[
+                {"lang":"de","url":"https://de.wikipedia.org/wiki/Rost","langname":"German","autonym":"Deutsch","title":"Rost"}
+            ]}}"#,
+        )
+        .unwrap();
+        let parse = res.parse.unwrap();
+        assert_eq!(parse.sections[1].number, "1.1");
+        assert_eq!(parse.sections[1].toclevel, 2);
+        assert_eq!(parse.sections[1].anchor, "Iron");
+        assert_eq!(parse.langlinks[0].lang, "de");
+        assert_eq!(parse.langlinks[0].title, "Rost");
+        assert_eq!(parse.langlinks[0].autonym, "Deutsch");
+        assert_eq!(
+            parse.wikitext,
+            "'''Rust''' is an \"iron oxide\".\n== Chemistry =="
+        );
+    }
+
+    #[test]
+    fn response_parse_redirect() {
+        let res: Response = serde_json::from_str(
+            r#"{"parse":{"title":"United States","pageid":3434750,
+                "redirects":[{"from":"USA","to":"United States","tofragment":"History"}],
+                "properties":{"wikibase_item":"Q30"},
+                "wikitext":""}}"#,
+        )
+        .unwrap();
+        let parse = res.parse.unwrap();
+        assert_eq!(parse.title, "United States");
+        assert_eq!(parse.redirects[0].from, "USA");
+        assert_eq!(parse.redirects[0].tofragment.as_deref(), Some("History"));
+        assert!(!parse.properties.contains_key("disambiguation"));
+    }
+
+    #[test]
+    fn response_error_and_warnings() {
+        let res: Response = serde_json::from_str(
+            r#"{"error":{"code":"missingtitle","info":"The page you specified doesn't exist."},
+                "warnings":{"main":{"warnings":"Unrecognized parameter: foo."}}}"#,
+        )
+        .unwrap();
+        let error = res.error.unwrap();
+        assert_eq!(error.code, "missingtitle");
+        assert_eq!(error.info, "The page you specified doesn't exist.");
+        assert_eq!(
+            res.warnings["main"].warnings,
+            "Unrecognized parameter: foo."
+        );
+        assert!(res.parse.is_none());
+    }
+}
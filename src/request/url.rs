@@ -1,91 +1,110 @@
-const BASE_URL: &str = "https://en.wikipedia.org/w/api.php?format=json";
-const PREFIX_SEARCH: &str = "action=query&list=prefixsearch";
-const PAGE_PARSE: &str = "action=parse&prop=wikitext%7Csections&formatversion=2";
+const BASE_URL: &str = "wikipedia.org/w/api.php";
+pub const DEFAULT_LANG: &str = "en";
 
 pub enum Endpoint {
     PrefixSearch,
+    FullTextSearch,
     PageParse,
+    /// A random article, the search is ignored
+    Random,
 }
 
+/// MediaWiki API modules
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Query,
+    Parse,
+}
+
+impl Action {
+    fn as_str(self) -> &'static str {
+        match self {
+            Action::Query => "query",
+            Action::Parse => "parse",
+        }
+    }
+}
+
+/// Builds an API url. Every value is percent-encoded, multi-value
+/// parameters such as `list` and `prop` are joined with `|`.
 #[cfg_attr(test, derive(Debug, PartialEq))]
-pub struct PrefixSearch<'a> {
+pub struct ApiQuery<'a> {
     url: &'a str,
-    params: &'a str,
-    search: &'a str,
-    limit: u16,
-    offset: u16,
+    lang: &'a str,
+    params: Vec<(&'a str, String)>,
 }
 
-impl Default for PrefixSearch<'_> {
-    fn default() -> Self {
+impl<'a> ApiQuery<'a> {
+    pub fn new(action: Action) -> Self {
         Self {
             url: BASE_URL,
-            params: PREFIX_SEARCH,
-            search: <&str as std::default::Default>::default(),
-            limit: u16::default(),
-            offset: u16::default(),
+            lang: DEFAULT_LANG,
+            params: vec![
+                ("format", "json".to_owned()),
+                ("formatversion", "2".to_owned()),
+                ("action", action.as_str().to_owned()),
+            ],
         }
     }
-}
 
-impl<'a> PrefixSearch<'a> {
-    pub fn new() -> Self {
-        Self::default()
+    pub fn lang(&mut self, lang: &'a str) -> &mut Self {
+        self.lang = lang;
+        self
     }
 
-    pub fn search(&mut self, search: &'a str) -> &mut Self {
-        self.search = search;
-        self
+    pub fn list(&mut self, list: &str) -> &mut Self {
+        self.append("list", list)
+    }
+
+    pub fn prop(&mut self, prop: &str) -> &mut Self {
+        self.append("prop", prop)
     }
 
-    pub fn limit(&mut self, limit: u16) -> &mut Self {
-        self.limit = limit;
+    /// Set `key`, replacing any previous value.
+    pub fn param<T: ToString>(&mut self, key: &'a str, value: T) -> &mut Self {
+        let value = value.to_string();
+        match self.params.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => *v = value,
+            None => self.params.push((key, value)),
+        }
         self
     }
 
-    pub fn offset(&mut self, offset: u16) -> &mut Self {
-        self.offset = offset;
+    /// Add `value` to the `|` separated values of `key`.
+    pub fn append(&mut self, key: &'a str, value: &str) -> &mut Self {
+        match self.params.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => {
+                v.push('|');
+                v.push_str(value);
+            }
+            None => self.params.push((key, value.to_owned())),
+        }
         self
     }
 
     pub fn parse(&self) -> String {
-        format!(
-            "{}&{}&pssearch={}&pslimit={}&psoffset={}",
-            self.url, self.params, self.search, self.limit, self.offset
-        )
+        let query = self
+            .params
+            .iter()
+            .map(|(key, value)| format!("{}={}", encode(key), encode(value)))
+            .collect::<Vec<_>>()
+            .join("&");
+        format!("https://{}.{}?{}", encode(self.lang), self.url, query)
     }
 }
 
-#[cfg_attr(test, derive(Debug, PartialEq))]
-pub struct PageParse<'a> {
-    url: &'a str,
-    params: &'a str,
-    page: &'a str,
-}
-
-impl Default for PageParse<'_> {
-    fn default() -> Self {
-        Self {
-            url: BASE_URL,
-            params: PAGE_PARSE,
-            page: <&str as std::default::Default>::default(),
+/// Percent-encode everything but the unreserved characters of RFC 3986.
+pub fn encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
         }
     }
-}
-
-impl<'a> PageParse<'a> {
-    pub fn new() -> Self {
-        Self::default()
-    }
-
-    pub fn page(&mut self, page: &'a str) -> &mut Self {
-        self.page = page;
-        self
-    }
-
-    pub fn parse(&self) -> String {
-        format!("{}&{}&page={}", self.url, self.params, self.page)
-    }
+    encoded
 }
 
 #[cfg(test)]
@@ -93,75 +112,49 @@ mod tests {
     use super::*;
 
     #[test]
-    fn prefix_search_new() {
-        assert_eq!(
-            PrefixSearch::new(),
-            PrefixSearch {
-                url: BASE_URL,
-                params: PREFIX_SEARCH,
-                search: <&str as std::default::Default>::default(),
-                limit: u16::default(),
-                offset: u16::default(),
-            }
-        )
-    }
-
-    #[test]
-    fn prefix_search_offset() {
+    fn api_query_new() {
         assert_eq!(
-            PrefixSearch::new().search("test").limit(7357).offset(7357),
-            &PrefixSearch {
-                url: BASE_URL,
-                params: PREFIX_SEARCH,
-                search: "test",
-                limit: 7357,
-                offset: 7357,
-            }
-        )
+            ApiQuery::new(Action::Parse).parse(),
+            "https://en.wikipedia.org/w/api.php?format=json&formatversion=2&action=parse"
+        );
     }
 
     #[test]
-    fn prefix_search_parse() {
-        let url = PrefixSearch::new()
-            .search("test")
-            .limit(7357)
-            .offset(7357)
+    fn api_query_list_and_params() {
+        let url = ApiQuery::new(Action::Query)
+            .lang("de")
+            .list("prefixsearch")
+            .param("pssearch", "test")
+            .param("pslimit", 7357)
+            .param("pslimit", 30)
             .parse();
-        let expected_url = format!(
-            "{}&{}&{}",
-            BASE_URL, PREFIX_SEARCH, "pssearch=test&pslimit=7357&psoffset=7357"
+        assert_eq!(
+            url,
+            "https://de.wikipedia.org/w/api.php?format=json&formatversion=2\
+             &action=query&list=prefixsearch&pssearch=test&pslimit=30"
         );
-        assert_eq!(url, expected_url)
     }
 
     #[test]
-    fn page_parse_new() {
-        assert_eq!(
-            PageParse::new(),
-            PageParse {
-                url: BASE_URL,
-                params: PAGE_PARSE,
-                page: <&str as std::default::Default>::default(),
-            }
-        )
+    fn api_query_multi_value() {
+        let url = ApiQuery::new(Action::Parse)
+            .prop("wikitext")
+            .prop("sections")
+            .parse();
+        assert!(url.ends_with("&action=parse&prop=wikitext%7Csections"));
     }
 
     #[test]
-    fn page_parse_page() {
-        assert_eq!(
-            PageParse::new().page("test"),
-            &PageParse {
-                url: BASE_URL,
-                params: PAGE_PARSE,
-                page: "test",
-            }
-        )
+    fn api_query_encodes_values() {
+        let url = ApiQuery::new(Action::Parse)
+            .param("page", "AT&T C# 100% Café")
+            .parse();
+        assert!(url.ends_with("&page=AT%26T%20C%23%20100%25%20Caf%C3%A9"));
     }
 
     #[test]
-    fn page_parse_parse() {
-        let url = PageParse::new().page("test").parse();
-        let expected_url = format!("{}&{}&{}", BASE_URL, PAGE_PARSE, "page=test");
-        assert_eq!(url, expected_url)
+    fn encode_unreserved() {
+        assert_eq!(encode("a-Z_0.9~"), "a-Z_0.9~");
+        assert_eq!(encode("a/b?c=d"), "a%2Fb%3Fc%3Dd");
     }
 }
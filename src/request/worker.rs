@@ -0,0 +1,72 @@
+use crate::request::Request;
+use crate::util::{Event, Key};
+use std::collections::HashMap;
+use std::sync::mpsc;
+use tokio::runtime::Runtime;
+use tokio::sync::oneshot;
+
+/// Runs requests on a tokio runtime so the UI thread never blocks. Each
+/// response is sent back to the event loop as `Event::Response` along with
+/// the id returned by `fetch`.
+pub struct Worker {
+    /// None for a worker that never fetches
+    runtime: Option<Runtime>,
+    tx: mpsc::Sender<Event<Key>>,
+    next_id: u64,
+    /// Dropping a sender cancels the matching request
+    pending: HashMap<u64, oneshot::Sender<()>>,
+}
+
+impl Worker {
+    pub fn new(tx: mpsc::Sender<Event<Key>>) -> Self {
+        Self {
+            runtime: Some(Runtime::new().expect("failed to start the tokio runtime")),
+            tx,
+            next_id: 0,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// A worker handing out ids without ever sending a request, for tests.
+    #[cfg(test)]
+    pub fn offline() -> Self {
+        Self {
+            runtime: None,
+            tx: mpsc::channel().0,
+            next_id: 0,
+            pending: HashMap::new(),
+        }
+    }
+
+    pub fn fetch(&mut self, req: Request) -> u64 {
+        self.next_id += 1;
+        let id = self.next_id;
+        let runtime = match &self.runtime {
+            Some(runtime) => runtime,
+            None => return id,
+        };
+        let (cancel_tx, cancel_rx) = oneshot::channel::<()>();
+        self.pending.insert(id, cancel_tx);
+
+        let tx = self.tx.clone();
+        runtime.spawn(async move {
+            tokio::select! {
+                res = req.fetch() => {
+                    let _ = tx.send(Event::Response(id, res.map(Box::new)));
+                }
+                _ = cancel_rx => {}
+            }
+        });
+        id
+    }
+
+    /// Abort a request still in flight. Its response will never be sent.
+    pub fn cancel(&mut self, id: u64) {
+        self.pending.remove(&id);
+    }
+
+    /// Forget about a request whose response has been received.
+    pub fn done(&mut self, id: u64) {
+        self.pending.remove(&id);
+    }
+}
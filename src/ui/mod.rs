@@ -1,2 +1,7 @@
+mod render;
+mod theme;
+#[allow(clippy::module_inception)]
 mod ui;
+pub use render::{render, Rendered};
+pub use theme::Theme;
 pub use ui::draw;
@@ -0,0 +1,620 @@
+use crate::ui::theme::{patch, Theme};
+use crate::wikitext::{Block, Document, Inline, ListKind, Table};
+use std::collections::HashMap;
+use tui::style::Style;
+use unicode_width::UnicodeWidthStr;
+
+/// A run of text sharing one style.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub text: String,
+    pub style: Style,
+    /// Index of the internal link this text belongs to
+    pub link: Option<usize>,
+}
+
+pub type Line = Vec<Span>;
+
+/// A document laid out for the Read pane.
+#[derive(Debug, Default)]
+pub struct Rendered {
+    pub lines: Vec<Line>,
+    /// Anchor of each section heading, as in links to it, and the line it
+    /// is on
+    pub headings: Vec<(String, usize)>,
+    /// Target of each internal link and the line it starts on
+    pub links: Vec<(String, usize)>,
+    /// For each row once wrapped, what was cut between it and the row above
+    /// if both come from the same line: a space, or nothing within a word
+    pub joins: Vec<Option<&'static str>>,
+}
+
+/// Lay out a document as styled lines for the Read pane.
+pub fn render(document: &Document, theme: &Theme) -> Rendered {
+    let mut renderer = Renderer {
+        theme,
+        lines: Vec::new(),
+        line: Vec::new(),
+        indent: String::new(),
+        sections: Vec::new(),
+        headings: Vec::new(),
+        anchors: HashMap::new(),
+        links: Vec::new(),
+        link: None,
+        refs: HashMap::new(),
+        ref_count: 0,
+    };
+    for block in &document.blocks {
+        renderer.block(block);
+    }
+    Rendered {
+        lines: renderer.lines,
+        headings: renderer.headings,
+        links: renderer.links,
+        joins: Vec::new(),
+    }
+}
+
+impl Rendered {
+    /// Break lines wider than `width` between words, so that every line is
+    /// one row of the Read pane. Heading and link lines become rows too.
+    pub fn wrap(self, width: usize) -> Self {
+        if width == 0 {
+            return self;
+        }
+        let mut rows = Vec::new();
+        let mut joins = Vec::new();
+        let mut first_rows = Vec::with_capacity(self.lines.len());
+        for line in self.lines {
+            first_rows.push(rows.len());
+            wrap_line(line, width, &mut rows, &mut joins);
+        }
+        let mut links = self.links;
+        let mut found = vec![false; links.len()];
+        for (row, line) in rows.iter().enumerate() {
+            for link in line.iter().filter_map(|span| span.link) {
+                if !found[link] {
+                    found[link] = true;
+                    links[link].1 = row;
+                }
+            }
+        }
+        Self {
+            lines: rows,
+            headings: self
+                .headings
+                .into_iter()
+                .map(|(title, line)| (title, first_rows[line]))
+                .collect(),
+            links,
+            joins,
+        }
+    }
+
+    /// Line of the heading `fragment` points at, as in `Title#fragment`.
+    /// Fragments may have spaces, or `.C3.A9` and `%C3%A9` escapes.
+    pub fn heading_line(&self, fragment: &str) -> Option<usize> {
+        let fragment = anchor(fragment);
+        let decoded = decode_fragment(&fragment);
+        [Some(fragment), decoded]
+            .iter()
+            .flatten()
+            .find_map(|fragment| {
+                self.headings
+                    .iter()
+                    .find(|(anchor, _)| anchor == fragment)
+                    .map(|(_, line)| *line)
+            })
+    }
+
+    /// Index of the section `line` is in, if it is past the first heading.
+    pub fn section_at(&self, line: usize) -> Option<usize> {
+        self.headings
+            .iter()
+            .rposition(|(_, heading_line)| *heading_line <= line)
+    }
+}
+
+/// Anchor of a heading titled `title`, words joined by underscores
+fn anchor(title: &str) -> String {
+    title.split_whitespace().collect::<Vec<_>>().join("_")
+}
+
+/// Undo the escapes of legacy anchors (`.C3.A9`) and URLs (`%C3%A9`), if
+/// there are any.
+fn decode_fragment(fragment: &str) -> Option<String> {
+    let bytes = fragment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escape = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'.' || bytes[i] == b'%')
+            .filter(|hex| {
+                hex.iter()
+                    .all(|c| c.is_ascii_digit() || (b'A'..=b'F').contains(c))
+            })
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escape {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded)
+        .ok()
+        .filter(|decoded| decoded != fragment)
+}
+
+fn wrap_line(
+    line: Line,
+    width: usize,
+    rows: &mut Vec<Line>,
+    joins: &mut Vec<Option<&'static str>>,
+) {
+    // The join of the row being filled is pushed before the row itself
+    joins.push(None);
+    let mut row = Vec::new();
+    let mut row_width = 0;
+    for span in line {
+        let mut rest = span.text.as_str();
+        while !rest.is_empty() {
+            // Alternate runs of spaces and words
+            let space = rest.starts_with(' ');
+            let end = rest
+                .find(|c: char| (c == ' ') != space)
+                .unwrap_or(rest.len());
+            let (token, tail) = rest.split_at(end);
+            rest = tail;
+            let token_width = token.width();
+            if row_width + token_width <= width {
+                push_span(&mut row, token, &span);
+                row_width += token_width;
+            } else if space {
+                break_row(rows, &mut row, joins, true);
+                row_width = 0;
+            } else {
+                if row_width > 0 {
+                    break_row(rows, &mut row, joins, false);
+                    row_width = 0;
+                }
+                // Words wider than the pane are cut anywhere
+                for c in token.chars() {
+                    let c_width = c.to_string().width();
+                    if row_width + c_width > width && row_width > 0 {
+                        break_row(rows, &mut row, joins, false);
+                        row_width = 0;
+                    }
+                    push_span(&mut row, &c.to_string(), &span);
+                    row_width += c_width;
+                }
+            }
+        }
+    }
+    rows.push(row);
+}
+
+/// End a row that continues on the next one, without its trailing spaces.
+/// `spaced` tells spaces were dropped at the break already.
+fn break_row(
+    rows: &mut Vec<Line>,
+    row: &mut Line,
+    joins: &mut Vec<Option<&'static str>>,
+    mut spaced: bool,
+) {
+    while let Some(last) = row.last_mut() {
+        let trimmed = last.text.trim_end_matches(' ').len();
+        spaced |= trimmed < last.text.len();
+        if trimmed > 0 {
+            last.text.truncate(trimmed);
+            break;
+        }
+        row.pop();
+    }
+    rows.push(std::mem::take(row));
+    joins.push(Some(if spaced { " " } else { "" }));
+}
+
+/// Append `text` to `row` with the style and link of `span`.
+fn push_span(row: &mut Line, text: &str, span: &Span) {
+    if let Some(last) = row.last_mut() {
+        if last.style == span.style && last.link == span.link {
+            last.text.push_str(text);
+            return;
+        }
+    }
+    row.push(Span {
+        text: text.to_owned(),
+        style: span.style,
+        link: span.link,
+    });
+}
+
+struct Renderer<'a> {
+    theme: &'a Theme,
+    lines: Vec<Line>,
+    line: Line,
+    /// Prefix of every line, e.g. for lists and quotes
+    indent: String,
+    /// Section counters, one per heading level below the current one
+    sections: Vec<usize>,
+    headings: Vec<(String, usize)>,
+    /// Headings seen so far with each anchor, to number duplicates
+    anchors: HashMap<String, usize>,
+    links: Vec<(String, usize)>,
+    /// Link the text being pushed belongs to
+    link: Option<usize>,
+    /// Footnote number of each named ref
+    refs: HashMap<String, usize>,
+    ref_count: usize,
+}
+
+impl Renderer<'_> {
+    fn push(&mut self, text: &str, style: Style) {
+        if text.is_empty() {
+            return;
+        }
+        if self.line.is_empty() && !self.indent.is_empty() {
+            let indent = self.indent.clone();
+            self.line.push(Span {
+                text: indent,
+                style: self.theme.quote,
+                link: None,
+            });
+        }
+        if let Some(last) = self.line.last_mut() {
+            if last.style == style && last.link == self.link {
+                last.text.push_str(text);
+                return;
+            }
+        }
+        self.line.push(Span {
+            text: text.to_owned(),
+            style,
+            link: self.link,
+        });
+    }
+
+    fn end_line(&mut self) {
+        self.lines.push(std::mem::take(&mut self.line));
+    }
+
+    fn blank_line(&mut self) {
+        if self.lines.last().is_some_and(|line| !line.is_empty()) {
+            self.lines.push(Vec::new());
+        }
+    }
+
+    fn block(&mut self, block: &Block) {
+        let theme = self.theme;
+        match block {
+            Block::Heading { level, content } => {
+                let depth = level.saturating_sub(2) as usize;
+                self.sections.resize(depth + 1, 0);
+                self.sections[depth] += 1;
+                let number = self
+                    .sections
+                    .iter()
+                    .map(|n| n.to_string())
+                    .collect::<Vec<_>>()
+                    .join(".");
+                let style = if depth == 0 {
+                    theme.heading
+                } else {
+                    theme.subheading
+                };
+                self.blank_line();
+                let number = format!("{} ", number);
+                self.push(&number, style);
+                self.inlines(content, style);
+                let text = self
+                    .line
+                    .iter()
+                    .map(|span| span.text.as_str())
+                    .collect::<String>();
+                let title = text.split_once(&number).map_or("", |(_, title)| title);
+                // Like MediaWiki, repeated headings get `_2`, `_3`...
+                let mut anchor = anchor(title);
+                let seen = self.anchors.entry(anchor.clone()).or_insert(0);
+                *seen += 1;
+                if *seen > 1 {
+                    anchor = format!("{}_{}", anchor, seen);
+                }
+                self.headings.push((anchor, self.lines.len()));
+                self.end_line();
+                self.lines.push(Vec::new());
+            }
+            Block::Paragraph(content) => {
+                self.inlines(content, theme.text);
+                self.end_line();
+                self.lines.push(Vec::new());
+            }
+            Block::List(items) => {
+                let mut numbers = Vec::new();
+                for item in items {
+                    numbers.resize(item.depth, 0);
+                    let marker = match item.kind {
+                        ListKind::Bullet => "• ".to_owned(),
+                        ListKind::Numbered => {
+                            numbers[item.depth - 1] += 1;
+                            format!("{}. ", numbers[item.depth - 1])
+                        }
+                        ListKind::Term | ListKind::Definition => String::new(),
+                    };
+                    let style = match item.kind {
+                        ListKind::Term => theme.bold,
+                        _ => theme.text,
+                    };
+                    self.push(&"  ".repeat(item.depth - 1), theme.text);
+                    self.push(&marker, theme.list_marker);
+                    self.inlines(&item.content, style);
+                    self.end_line();
+                }
+                self.lines.push(Vec::new());
+            }
+            Block::Table(table) => {
+                self.table(table);
+                self.lines.push(Vec::new());
+            }
+            Block::Quote(blocks) => {
+                let indent = self.indent.len();
+                self.indent.push_str("  │ ");
+                for each in blocks {
+                    self.block(each);
+                }
+                self.indent.truncate(indent);
+            }
+            Block::Template(_) => {}
+            Block::Rule => {
+                self.push(&"─".repeat(20), theme.table_border);
+                self.end_line();
+                self.lines.push(Vec::new());
+            }
+        }
+    }
+
+    fn table(&mut self, table: &Table) {
+        let theme = self.theme;
+        if let Some(caption) = &table.caption {
+            self.inlines(caption, theme.table_header);
+            self.end_line();
+        }
+        for row in &table.rows {
+            for (i, cell) in row.iter().enumerate() {
+                if i > 0 {
+                    self.push(" │ ", theme.table_border);
+                }
+                let style = if cell.header {
+                    theme.table_header
+                } else {
+                    theme.text
+                };
+                self.inlines(&cell.content, style);
+            }
+            self.end_line();
+        }
+    }
+
+    fn inlines(&mut self, content: &[Inline], style: Style) {
+        let style = if self.indent.is_empty() {
+            style
+        } else {
+            patch(style, self.theme.quote)
+        };
+        for inline in content {
+            self.inline(inline, style);
+        }
+    }
+
+    fn inline(&mut self, inline: &Inline, style: Style) {
+        let theme = self.theme;
+        match inline {
+            Inline::Text(text) => {
+                // Cells may hold several lines, everything else is on one
+                let mut lines = text.split('\n');
+                if let Some(first) = lines.next() {
+                    self.push(first, style);
+                }
+                for each in lines {
+                    self.push(" ", style);
+                    self.push(each, style);
+                }
+            }
+            Inline::Bold(content) => {
+                for each in content {
+                    self.inline(each, patch(style, theme.bold));
+                }
+            }
+            Inline::Italic(content) => {
+                for each in content {
+                    self.inline(each, patch(style, theme.italic));
+                }
+            }
+            Inline::Link { target, label } => {
+                self.link = Some(self.links.len());
+                self.links.push((target.to_owned(), self.lines.len()));
+                for each in label {
+                    self.inline(each, patch(style, theme.link));
+                }
+                self.link = None;
+            }
+            Inline::ExternalLink { url, label } => {
+                let style = patch(style, theme.external_link);
+                if label.is_empty() {
+                    self.push(url, style);
+                }
+                for each in label {
+                    self.inline(each, style);
+                }
+            }
+            Inline::Ref { name, .. } => {
+                let number = match name.as_ref().and_then(|name| self.refs.get(name)) {
+                    Some(number) => *number,
+                    None => {
+                        self.ref_count += 1;
+                        if let Some(name) = name {
+                            self.refs.insert(name.to_owned(), self.ref_count);
+                        }
+                        self.ref_count
+                    }
+                };
+                self.push(&format!("[{}]", number), theme.reference);
+            }
+            Inline::Template(_) => {}
+            Inline::LineBreak => self.end_line(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wikitext;
+
+    fn text(lines: &[Line]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|line| line.iter().map(|span| span.text.as_str()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn lines_numbers_sections_and_refs() {
+        let document = wikitext::parse(
+            "Intro<ref name=\"a\">x</ref>.\n== One ==\n=== Sub ===\nText<ref name=\"a\" /><ref>y</ref>\n== Two ==",
+        );
+        let lines = render(&document, &Theme::default()).lines;
+        assert_eq!(
+            text(&lines),
+            vec![
+                "Intro[1].",
+                "",
+                "1 One",
+                "",
+                "1.1 Sub",
+                "",
+                "Text[1][2]",
+                "",
+                "2 Two",
+                ""
+            ]
+        );
+    }
+
+    #[test]
+    fn render_records_headings() {
+        let document =
+            wikitext::parse("Intro\n== One ==\nText\n=== ''Sub'' ===\n== One ==\n== Two  words ==");
+        let rendered = render(&document, &Theme::default());
+        assert_eq!(
+            rendered.headings,
+            vec![
+                ("One".to_owned(), 2),
+                ("Sub".to_owned(), 6),
+                ("One_2".to_owned(), 8),
+                ("Two_words".to_owned(), 10)
+            ]
+        );
+        assert_eq!(rendered.section_at(1), None);
+        assert_eq!(rendered.section_at(5), Some(0));
+        assert_eq!(rendered.section_at(7), Some(1));
+        assert_eq!(rendered.heading_line("Two words"), Some(10));
+        assert_eq!(rendered.heading_line("One_2"), Some(8));
+        assert_eq!(rendered.heading_line("Three"), None);
+        assert_eq!(rendered.section_at(9), Some(2));
+    }
+
+    #[test]
+    fn heading_line_decodes_fragments() {
+        let document = wikitext::parse("== Café ==\n== Café ==\n== 1.5A ==");
+        let rendered = render(&document, &Theme::default());
+        assert_eq!(rendered.heading_line("Caf.C3.A9"), Some(0));
+        assert_eq!(rendered.heading_line("Caf%C3%A9_2"), Some(2));
+        assert_eq!(rendered.heading_line("1.5A"), Some(4));
+    }
+
+    #[test]
+    fn render_records_links() {
+        let document = wikitext::parse("[[A]] and [[B|b]]\n\nText [[C#Part|c]]");
+        let rendered = render(&document, &Theme::default());
+        assert_eq!(
+            rendered.links,
+            vec![
+                ("A".to_owned(), 0),
+                ("B".to_owned(), 0),
+                ("C#Part".to_owned(), 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn wrap_between_words() {
+        let document =
+            wikitext::parse("== Head ==\nOne two three [[four five]] six\n\nabcdefghijkl");
+        let rendered = render(&document, &Theme::default()).wrap(9);
+        assert_eq!(
+            text(&rendered.lines),
+            vec![
+                "1 Head",
+                "",
+                "One two",
+                "three",
+                "four five",
+                "six",
+                "",
+                "abcdefghi",
+                "jkl",
+                ""
+            ]
+        );
+        assert_eq!(rendered.headings, vec![("Head".to_owned(), 0)]);
+        assert_eq!(rendered.links, vec![("four five".to_owned(), 4)]);
+        assert_eq!(
+            rendered.joins,
+            vec![
+                None,
+                None,
+                None,
+                Some(" "),
+                Some(" "),
+                Some(" "),
+                None,
+                None,
+                Some(""),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn lines_style_inlines() {
+        let theme = Theme::default();
+        let document = wikitext::parse("'''Bold''' [[Link]]");
+        let lines = render(&document, &theme).lines;
+        assert_eq!(
+            lines[0],
+            vec![
+                Span {
+                    text: "Bold".to_owned(),
+                    style: theme.bold,
+                    link: None,
+                },
+                Span {
+                    text: " ".to_owned(),
+                    style: theme.text,
+                    link: None,
+                },
+                Span {
+                    text: "Link".to_owned(),
+                    style: theme.link,
+                    link: Some(0),
+                },
+            ]
+        );
+    }
+}
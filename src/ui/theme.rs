@@ -0,0 +1,113 @@
+use tui::style::{Color, Modifier, Style};
+
+/// Styles of the article elements shown in the Read pane.
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    pub text: Style,
+    /// Top level (`== x ==`) section headings
+    pub heading: Style,
+    /// Headings of subsections
+    pub subheading: Style,
+    pub bold: Style,
+    pub italic: Style,
+    pub link: Style,
+    /// Link chosen with Tab, opened by Enter
+    pub selected_link: Style,
+    pub external_link: Style,
+    /// `[1]` markers of footnotes
+    pub reference: Style,
+    pub quote: Style,
+    pub list_marker: Style,
+    pub table_header: Style,
+    pub table_border: Style,
+    /// Matches of an in-article find
+    pub find_match: Style,
+    pub find_current: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            text: Style::default(),
+            heading: Style::default()
+                .fg(Color::Yellow)
+                .modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            subheading: Style::default().fg(Color::Yellow).modifier(Modifier::BOLD),
+            bold: Style::default().modifier(Modifier::BOLD),
+            italic: Style::default().modifier(Modifier::ITALIC),
+            link: Style::default()
+                .fg(Color::Cyan)
+                .modifier(Modifier::UNDERLINED),
+            selected_link: Style::default().fg(Color::Black).bg(Color::Cyan),
+            external_link: Style::default()
+                .fg(Color::Blue)
+                .modifier(Modifier::UNDERLINED),
+            reference: Style::default().fg(Color::DarkGray),
+            quote: Style::default().fg(Color::Gray).modifier(Modifier::ITALIC),
+            list_marker: Style::default().fg(Color::Green),
+            table_header: Style::default().modifier(Modifier::BOLD),
+            table_border: Style::default().fg(Color::DarkGray),
+            find_match: Style::default().fg(Color::Black).bg(Color::Yellow),
+            find_current: Style::default()
+                .fg(Color::Black)
+                .bg(Color::LightRed)
+                .modifier(Modifier::BOLD),
+        }
+    }
+}
+
+impl Theme {
+    /// Names accepted by `named`
+    pub const NAMES: [&'static str; 2] = ["dark", "light"];
+
+    /// A built-in theme, `dark` being the default one.
+    pub fn named(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::default()),
+            "light" => Some(Self::light()),
+            _ => None,
+        }
+    }
+
+    /// Colours readable on a light background
+    fn light() -> Self {
+        Self {
+            heading: Style::default()
+                .fg(Color::Magenta)
+                .modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            subheading: Style::default().fg(Color::Magenta).modifier(Modifier::BOLD),
+            link: Style::default()
+                .fg(Color::Blue)
+                .modifier(Modifier::UNDERLINED),
+            selected_link: Style::default().fg(Color::White).bg(Color::Blue),
+            external_link: Style::default()
+                .fg(Color::Cyan)
+                .modifier(Modifier::UNDERLINED),
+            reference: Style::default().fg(Color::Gray),
+            quote: Style::default()
+                .fg(Color::DarkGray)
+                .modifier(Modifier::ITALIC),
+            list_marker: Style::default().fg(Color::Red),
+            table_border: Style::default().fg(Color::Gray),
+            ..Self::default()
+        }
+    }
+}
+
+/// Apply `style` on top of `base`: colours are replaced unless reset and
+/// modifiers are combined.
+pub fn patch(base: Style, style: Style) -> Style {
+    Style {
+        fg: if style.fg == Color::Reset {
+            base.fg
+        } else {
+            style.fg
+        },
+        bg: if style.bg == Color::Reset {
+            base.bg
+        } else {
+            style.bg
+        },
+        modifier: base.modifier | style.modifier,
+    }
+}
@@ -1,17 +1,38 @@
-use crate::app::{App, Mode};
+use crate::app::{App, Focus, Loading, Match, Mode};
+use crate::config::{Action, Keymap};
+use crate::request::SearchResult;
+use crate::ui::render::Rendered;
+use crate::ui::theme::patch;
 use tui::{
     backend::Backend,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    widgets::{Block, BorderType, Borders, List, Paragraph, Text},
+    widgets::{Block, BorderType, Borders, Clear, List, ListState, Paragraph, Tabs, Text},
     Frame,
 };
+use unicode_width::UnicodeWidthStr;
 
-pub fn draw<B: Backend>(f: &mut Frame<B>, app: &mut App) {
+pub fn draw<B: Backend>(f: &mut Frame<B>, app: &mut App, keymap: &Keymap) {
     let size = f.size();
     let chunks = Layout::default()
-        .direction(Direction::Horizontal)
+        .direction(Direction::Vertical)
         .margin(1)
+        .constraints([Constraint::Min(1), Constraint::Length(1)].as_ref())
+        .split(size);
+
+    draw_main_panels(f, app, keymap, chunks[0]);
+    draw_status(f, app, keymap, chunks[1]);
+    if app.picker.is_some() {
+        draw_picker(f, app, size);
+    }
+    if app.error.is_some() {
+        draw_error(f, app, keymap, size);
+    }
+}
+
+fn draw_main_panels<B: Backend>(f: &mut Frame<B>, app: &mut App, keymap: &Keymap, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
         .constraints(
             [
                 Constraint::Percentage(30),
@@ -20,13 +41,16 @@ pub fn draw<B: Backend>(f: &mut Frame<B>, app: &mut App) {
             ]
             .as_ref(),
         )
-        .split(size);
+        .split(area);
 
-    draw_left_panels(f, app, chunks[0]);
-    draw_right_panels(f, app, chunks[2]);
+    // Lay the article out once for both the Read pane and the Table of Content
+    app.page_width = chunks[2].width.saturating_sub(2);
+    let rendered = app.rendered();
+    draw_left_panels(f, app, &rendered, chunks[0]);
+    draw_right_panels(f, app, keymap, &rendered, chunks[2]);
 }
 
-fn draw_left_panels<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
+fn draw_left_panels<B: Backend>(f: &mut Frame<B>, app: &mut App, rendered: &Rendered, area: Rect) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints(
@@ -41,15 +65,22 @@ fn draw_left_panels<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
 
     draw_search_and_mode(f, app, chunks[0]);
     draw_page_list(f, app, chunks[1]);
-    draw_content(f, app, chunks[2]);
+    draw_content(f, app, rendered, chunks[2]);
 }
 
-fn draw_right_panels<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
+fn draw_right_panels<B: Backend>(
+    f: &mut Frame<B>,
+    app: &mut App,
+    keymap: &Keymap,
+    rendered: &Rendered,
+    area: Rect,
+) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints(
             [
-                Constraint::Length(3),
+                Constraint::Length(6),
+                Constraint::Length(1),
                 Constraint::Min(1),
                 Constraint::Length(3),
             ]
@@ -57,9 +88,10 @@ fn draw_right_panels<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
         )
         .split(area);
 
-    draw_help(f, app, chunks[0]);
-    draw_page(f, app, chunks[1]);
-    draw_url(f, app, chunks[2]);
+    draw_help(f, app, keymap, chunks[0]);
+    draw_tabs(f, app, chunks[1]);
+    draw_page(f, app, rendered, chunks[2]);
+    draw_url(f, app, chunks[3]);
 }
 
 fn draw_search_and_mode<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
@@ -73,64 +105,173 @@ fn draw_search_and_mode<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect)
 }
 
 fn draw_search<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
+    app.panels.search = area;
+    let (visible, cursor) = match &app.reverse_search {
+        Some(search) => {
+            let found = search.found.and_then(|i| app.queries.get(i));
+            let failing = if found.is_none() && !search.query.is_empty() {
+                "failing "
+            } else {
+                ""
+            };
+            let prompt = format!("({}reverse-i-search)'{}", failing, search.query);
+            let cursor = prompt.width();
+            (
+                format!("{}': {}", prompt, found.unwrap_or_default()),
+                cursor,
+            )
+        }
+        None => {
+            let (visible, cursor) = app.search_input.view(area.width.saturating_sub(2) as usize);
+            (visible.to_owned(), cursor)
+        }
+    };
+    app.search_cursor_x =
+        area.left() + cursor.min(area.width.saturating_sub(3) as usize) as u16 + 1;
+    app.search_cursor_y = area.top() + 1;
     let text = [Text::styled(
-        &app.search_input,
+        visible,
         Style::default().fg(Color::Green).modifier(Modifier::BOLD),
     )];
-    let modifier = match app.mode {
-        Mode::Search => Modifier::empty(),
-        Mode::Browse => Modifier::DIM,
-        Mode::Read => Modifier::DIM,
+    let modifier = match app.focus {
+        Focus::Search => Modifier::empty(),
+        Focus::Browse => Modifier::DIM,
+        Focus::Read | Focus::Toc | Focus::Url => Modifier::DIM,
     };
-    let input = Paragraph::new(text.iter())
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_type(BorderType::Rounded)
-                .border_style(Style::default().modifier(modifier))
-                .title("Search")
-                .style(Style::default().modifier(modifier)),
-        )
-        .wrap(true);
-    app.search_cursor_x_max = area.right() - 4;
-    app.search_cursor_x = area.left() + app.search_input.len() as u16 + 1;
-    app.search_cursor_y = area.top() + 1;
+    let title = if app.full_text {
+        format!("Search ({}, full text)", app.lang)
+    } else {
+        format!("Search ({})", app.lang)
+    };
+    let input = Paragraph::new(text.iter()).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(border_style(app.focus == Focus::Search, modifier))
+            .title(&title)
+            .style(Style::default().modifier(modifier)),
+    );
     f.render_widget(input, area);
 }
 
 fn draw_page_list<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
-    let pages = app.pages.items.iter().map(|item| Text::raw(item));
-    let pages = List::new(pages)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_type(BorderType::Rounded)
-                .border_style(Style::default().modifier(match app.mode {
-                    Mode::Search => Modifier::DIM,
-                    Mode::Browse => Modifier::empty(),
-                    Mode::Read => Modifier::empty(),
-                }))
-                .title("Page"),
-        )
-        .style(Style::default().modifier(match app.mode {
-            Mode::Search => Modifier::DIM,
-            Mode::Browse => Modifier::empty(),
-            Mode::Read => Modifier::DIM,
-        }))
-        .highlight_style(Style::default().fg(Color::Green).modifier(match app.mode {
-            Mode::Search => Modifier::DIM,
-            Mode::Browse => Modifier::BOLD,
-            Mode::Read => Modifier::DIM,
-        }))
-        .highlight_symbol("> ");
-    f.render_stateful_widget(pages, area, &mut app.pages.state);
+    app.panels.pages = area;
+    let style = Style::default().modifier(match app.focus {
+        Focus::Search => Modifier::DIM,
+        Focus::Browse => Modifier::empty(),
+        Focus::Read | Focus::Toc | Focus::Url => Modifier::DIM,
+    });
+    let highlight_style = Style::default().fg(Color::Green).modifier(match app.focus {
+        Focus::Search => Modifier::DIM,
+        Focus::Browse => Modifier::BOLD,
+        Focus::Read | Focus::Toc | Focus::Url => Modifier::DIM,
+    });
+    let count = app.pages.items.len();
+    let title = match (app.pages_total, app.pages_next) {
+        (Some(total), _) => format!("Page ({} of {} results)", count, total),
+        (None, Some(_)) => format!("Page ({}+ results)", count),
+        (None, None) if count > 0 => format!("Page ({} results)", count),
+        (None, None) => "Page".to_owned(),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(border_style(
+            app.focus == Focus::Browse,
+            match app.focus {
+                Focus::Search => Modifier::DIM,
+                Focus::Browse => Modifier::empty(),
+                Focus::Read | Focus::Toc | Focus::Url => Modifier::empty(),
+            },
+        ))
+        .title(&title);
+
+    // Full-text results take two rows, so scroll by hand to keep the
+    // selected one in view.
+    let pages = &mut app.pages;
+    let height = block.inner(area).height as usize;
+    let rows = |result: &SearchResult| if result.snippet.is_empty() { 1 } else { 2 };
+    let selected = pages.state.selected();
+    if let Some(selected) = selected {
+        pages.offset = pages.offset.min(selected);
+        while pages.offset < selected
+            && pages.items[pages.offset..=selected]
+                .iter()
+                .map(rows)
+                .sum::<usize>()
+                > height
+        {
+            pages.offset += 1;
+        }
+    }
+    pages.offset = pages.offset.min(pages.items.len());
+
+    let mut text = Vec::new();
+    let mut used = 0;
+    for (i, result) in pages.items.iter().enumerate().skip(pages.offset) {
+        if used >= height {
+            break;
+        }
+        used += rows(result);
+        page_list_item(
+            &mut text,
+            result,
+            selected == Some(i),
+            style,
+            highlight_style,
+        );
+    }
+    let paragraph = Paragraph::new(text.iter())
+        .block(block)
+        .style(style)
+        .wrap(false);
+    f.render_widget(paragraph, area);
+}
+
+/// Append one search result to the page list: the title, and for full-text
+/// results size, last edit and the snippet with matched terms highlighted.
+fn page_list_item<'t>(
+    text: &mut Vec<Text<'t>>,
+    result: &'t SearchResult,
+    selected: bool,
+    style: Style,
+    highlight_style: Style,
+) {
+    let title_style = if selected { highlight_style } else { style };
+    text.push(Text::styled(
+        if selected { "> " } else { "  " },
+        title_style,
+    ));
+    text.push(Text::styled(result.title.as_str(), title_style));
+    let dim = Style::default().modifier(style.modifier | Modifier::DIM);
+    if let Some(wordcount) = result.wordcount {
+        text.push(Text::styled(format!(" · {} words", wordcount), dim));
+    }
+    if let Some(date) = result.timestamp.as_ref().and_then(|t| t.get(..10)) {
+        text.push(Text::styled(format!(" · {}", date), dim));
+    }
+    text.push(Text::raw("\n"));
+    if result.snippet.is_empty() {
+        return;
+    }
+    let matched = Style::default()
+        .fg(Color::Yellow)
+        .modifier(style.modifier | Modifier::BOLD);
+    text.push(Text::styled("  ", style));
+    for (part, is_match) in result.snippet_parts() {
+        text.push(Text::styled(part, if is_match { matched } else { style }));
+    }
+    text.push(Text::raw("\n"));
 }
 
 fn draw_mode<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
-    let text = match app.mode {
+    let text = match app.mode() {
         Mode::Search => [Text::raw("Search")],
         Mode::Browse => [Text::raw("Browse")],
         Mode::Read => [Text::raw("Read")],
+        Mode::Toc => [Text::raw("Contents")],
+        Mode::Url => [Text::raw("URL")],
+        Mode::Command => [Text::raw("Command")],
     };
     let paragraph = Paragraph::new(text.iter())
         .block(
@@ -143,21 +284,128 @@ fn draw_mode<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
     f.render_widget(paragraph, area);
 }
 
-fn draw_help<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
-    let text = match app.mode {
-        Mode::Search => [
-            Text::raw(" Press any key to type. "),
-            Text::raw("Enter to search."),
+/// Rows of the help panel for `mode`: actions and what their keys do
+fn help(mode: Mode) -> &'static [&'static [(&'static [Action], &'static str)]] {
+    use Action::*;
+    match mode {
+        Mode::Search => &[
+            &[
+                (&[], "Type a title"),
+                (&[Enter], "to search"),
+                (&[FullText], "for full text"),
+                (&[FocusNext, FocusPrevious], "for panels"),
+            ],
+            &[
+                (&[Up, Down], "for earlier searches"),
+                (&[ReverseSearch], "to find one"),
+                (&[Language], "for languages"),
+            ],
+            &[(&[Left, Right, WordLeft, WordRight], "to move")],
+            &[
+                (&[Home, End, LineStart, LineEnd], "for the ends"),
+                (&[DeleteWord, DeleteToStart], "to delete"),
+            ],
         ],
-        Mode::Browse => [
-            Text::raw(" Up & Down to navigate. Left & Right to jump. "),
-            Text::raw("Esc to go back to search mode."),
+        Mode::Browse => &[
+            &[
+                (&[Up, Down], "to navigate"),
+                (&[Left, Right, PageUp, PageDown, Home, End], "to jump"),
+            ],
+            &[
+                (&[Enter], "to read"),
+                (&[FocusSearch, Escape], "to search"),
+                (&[Command], "for commands"),
+                (&[FocusNext, FocusPrevious], "for panels"),
+            ],
         ],
-        Mode::Read => [
-            Text::raw(" Up & Down to navigate. Left & Right to jump. "),
-            Text::raw("Esc to go back to browse mode."),
+        Mode::Read => &[
+            &[
+                (&[Up, Down, PageUp, PageDown, Home, End], "to scroll"),
+                (&[Contents], "for contents"),
+                (&[Escape], "to browse"),
+            ],
+            &[
+                (&[Left, Right, HalfPageUp, HalfPageDown], "by half a page"),
+                (&[Back, Forward], "for back and forward"),
+            ],
+            &[
+                (&[PreviousLink, NextLink], "to select a link"),
+                (&[Enter], "to open it"),
+                (&[OpenInNewTab], "in a new tab"),
+                (&[NewTab, CloseTab, PreviousTab, NextTab], "for tabs"),
+            ],
+            &[
+                (&[Find, FindNext, FindPrevious], "to find"),
+                (&[LanguageLinks], "for languages"),
+                (&[Command], "for commands"),
+                (&[FocusNext, FocusPrevious], "for panels"),
+            ],
         ],
-    };
+        Mode::Toc => &[
+            &[
+                (&[Up, Down], "to pick a section"),
+                (&[Enter], "to jump to it"),
+                (&[Escape], "to read"),
+            ],
+            &[(&[FocusNext, FocusPrevious], "for panels")],
+        ],
+        Mode::Url => &[
+            &[(&[Left, Right, Home, End], "to scroll the URL")],
+            &[(&[FocusNext, FocusPrevious], "for panels")],
+        ],
+        Mode::Command => &[
+            &[(
+                &[],
+                "open <title>, lang <code>, random, export md, bookmark, bookmarks",
+            )],
+            &[(&[], "set theme dark|light, quit")],
+            &[
+                (&[FocusNext], "to complete"),
+                (&[Up, Down], "for earlier commands"),
+                (&[Enter], "to run"),
+                (&[Escape], "to cancel"),
+            ],
+        ],
+    }
+}
+
+/// Describe the keys of `mode` as bound in `keymap`, one sentence per entry
+/// of `help`, leaving out actions no key is bound to.
+fn help_text(keymap: &Keymap, mode: Mode) -> String {
+    let rows: Vec<String> = help(mode)
+        .iter()
+        .map(|row| {
+            let sentences: Vec<String> = row
+                .iter()
+                .filter_map(|(actions, what)| {
+                    if actions.is_empty() {
+                        return Some(format!("{}.", what));
+                    }
+                    let keys = key_names(keymap, mode, actions);
+                    if keys.is_empty() {
+                        None
+                    } else {
+                        Some(format!("{} {}.", keys.join(", "), what))
+                    }
+                })
+                .collect();
+            format!(" {}", sentences.join(" "))
+        })
+        .collect();
+    rows.join("\n")
+}
+
+/// Names of the keys bound to any of `actions` in `mode`
+fn key_names(keymap: &Keymap, mode: Mode, actions: &[Action]) -> Vec<String> {
+    actions
+        .iter()
+        .flat_map(|action| keymap.keys(mode, *action))
+        .map(|key| key.to_string())
+        .collect()
+}
+
+fn draw_help<B: Backend>(f: &mut Frame<B>, app: &mut App, keymap: &Keymap, area: Rect) {
+    let text = [Text::raw(help_text(keymap, app.mode()))];
     let paragraph = Paragraph::new(text.iter())
         .block(
             Block::default()
@@ -165,52 +413,373 @@ fn draw_help<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded),
         )
-        .alignment(Alignment::Left);
+        .alignment(Alignment::Left)
+        .wrap(true);
     f.render_widget(paragraph, area);
 }
 
-fn draw_content<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
-    let text = app.toc.iter().map(|item| Text::raw(item));
-    let modifier = match app.mode {
-        Mode::Search => Modifier::DIM,
-        Mode::Browse => Modifier::empty(),
-        Mode::Read => Modifier::empty(),
+fn draw_content<B: Backend>(f: &mut Frame<B>, app: &mut App, rendered: &Rendered, area: Rect) {
+    app.panels.toc = area;
+    let focused = app.focus == Focus::Toc;
+    let modifier = match app.focus {
+        Focus::Search => Modifier::DIM,
+        Focus::Browse => Modifier::empty(),
+        Focus::Read | Focus::Toc | Focus::Url => Modifier::empty(),
     };
-    let paragraph = List::new(text).block(
-        Block::default()
-            .title("Table of Content")
-            .borders(Borders::ALL)
-            .border_style(Style::default().modifier(modifier))
-            .border_type(BorderType::Rounded)
-            .style(Style::default().modifier(modifier)),
-    );
-    f.render_widget(paragraph, area);
+    // Outside of Toc mode, follow the section being read
+    let (highlight_style, selected) = match app.focus {
+        Focus::Toc => (
+            Style::default().fg(Color::Green).modifier(Modifier::BOLD),
+            app.current_tab().toc.state.selected(),
+        ),
+        _ => (
+            Style::default().fg(Color::Green),
+            app.current_tab()
+                .section_at(rendered, app.current_tab().scroll),
+        ),
+    };
+
+    // Scroll by hand, like the Page list, so clicks can tell which entry
+    // is on which row.
+    let toc = &mut app.tabs[app.tab].toc;
+    let height = (area.height.saturating_sub(2) as usize).max(1);
+    if let Some(selected) = selected {
+        if selected >= toc.offset + height {
+            toc.offset = selected + 1 - height;
+        } else if selected < toc.offset {
+            toc.offset = selected;
+        }
+    }
+    toc.offset = toc.offset.min(toc.items.len());
+    let mut state = ListState::default();
+    state.select(selected.map(|selected| selected - toc.offset));
+    let text = toc
+        .items
+        .iter()
+        .skip(toc.offset)
+        .map(|(label, _)| Text::raw(label));
+    let list = List::new(text)
+        .block(
+            Block::default()
+                .title("Table of Content")
+                .borders(Borders::ALL)
+                .border_style(border_style(focused, modifier))
+                .border_type(BorderType::Rounded)
+                .style(Style::default().modifier(modifier)),
+        )
+        .highlight_style(highlight_style);
+    f.render_stateful_widget(list, area, &mut state);
 }
 
-fn draw_page<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
-    let page = app.page.iter().map(|x| Text::raw(x)).collect::<Vec<_>>();
+fn draw_tabs<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
+    let titles = app
+        .tabs
+        .iter()
+        .enumerate()
+        .map(|(i, tab)| {
+            let title = if tab.title.is_empty() {
+                "New tab"
+            } else {
+                &tab.title
+            };
+            format!("{} {}", i + 1, title)
+        })
+        .collect::<Vec<_>>();
+    let tabs = Tabs::default()
+        .titles(&titles)
+        .select(app.tab)
+        .style(Style::default().modifier(Modifier::DIM))
+        .highlight_style(Style::default().fg(Color::Green).modifier(Modifier::BOLD));
+    f.render_widget(tabs, area);
+}
+
+fn draw_page<B: Backend>(f: &mut Frame<B>, app: &mut App, rendered: &Rendered, area: Rect) {
+    app.panels.page = area;
+    app.page_height = area.height.saturating_sub(2);
+    let focused = app.focus == Focus::Read;
+    let height = app.page_height as usize;
+    let rows = rendered.lines.len();
+    let max_scroll = rows.saturating_sub(height);
+    let tab = &mut app.tabs[app.tab];
+    tab.scroll = tab.scroll.min(max_scroll);
+
+    let theme = &app.theme;
+    let selected = tab.link;
+    let (matches, current): (&[Match], _) = match &app.find {
+        Some(find) => (&find.matches, Some(find.current)),
+        None => (&[], None),
+    };
+    let mut page = Vec::new();
+    for (row, line) in rendered
+        .lines
+        .iter()
+        .enumerate()
+        .skip(tab.scroll)
+        .take(height)
+    {
+        // Style of each byte range of the row, matches on top of links
+        let mut offset = 0;
+        for span in line {
+            let mut style = span.style;
+            if span.link.is_some() && span.link == selected {
+                style = patch(style, theme.selected_link);
+            }
+            let mut ranges = vec![(offset, offset + span.text.len(), style)];
+            for m in matches.iter().filter(|m| m.row == row) {
+                let style = if Some(m.index) == current {
+                    theme.find_current
+                } else {
+                    theme.find_match
+                };
+                ranges = overlay(ranges, m.start, m.end, style);
+            }
+            for (start, end, style) in ranges {
+                page.push(Text::styled(
+                    &span.text[start - offset..end - offset],
+                    style,
+                ));
+            }
+            offset += span.text.len();
+        }
+        page.push(Text::raw("\n"));
+    }
+
+    // Position in the border, like a pager
+    let position = if max_scroll == 0 {
+        "All".to_owned()
+    } else if tab.scroll == 0 {
+        "Top".to_owned()
+    } else if tab.scroll == max_scroll {
+        "Bot".to_owned()
+    } else {
+        format!("{}%", tab.scroll * 100 / max_scroll)
+    };
+    let border_style = border_style(focused, Modifier::empty());
     let paragraph = Paragraph::new(page.iter())
         .block(
             Block::default()
+                .title(&position)
                 .borders(Borders::ALL)
-                .border_type(BorderType::Rounded),
+                .border_type(BorderType::Rounded)
+                .border_style(border_style),
         )
         .alignment(Alignment::Left)
-        .wrap(true)
-        .scroll(app.page_scroll);
+        .wrap(false);
     f.render_widget(paragraph, area);
+
+    // Scrollbar thumb over the right border
+    if max_scroll > 0 && height > 0 {
+        let thumb = (height * height / rows).max(1);
+        let top = tab.scroll * (height - thumb) / max_scroll;
+        let text = [Text::styled("┃\n".repeat(thumb), border_style)];
+        let thumb_area = Rect::new(
+            area.right() - 1,
+            area.top() + 1 + top as u16,
+            1,
+            thumb as u16,
+        );
+        f.render_widget(Paragraph::new(text.iter()), thumb_area);
+    }
 }
 
 fn draw_url<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
-    let text = [Text::raw(app.url.to_owned())];
+    app.panels.url = area;
+    let url = &app.tabs[app.tab].url;
+    app.url_scroll = app.url_scroll.min(url.chars().count().saturating_sub(1));
+    let text = [Text::raw(
+        url.chars().skip(app.url_scroll).collect::<String>(),
+    )];
     let paragraph = Paragraph::new(text.iter())
         .block(
             Block::default()
                 .title("URL")
                 .borders(Borders::ALL)
-                .border_type(BorderType::Rounded),
+                .border_type(BorderType::Rounded)
+                .border_style(border_style(app.focus == Focus::Url, Modifier::empty())),
         )
         .alignment(Alignment::Left)
         .wrap(false);
     f.render_widget(paragraph, area);
 }
+
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+fn draw_status<B: Backend>(f: &mut Frame<B>, app: &mut App, keymap: &Keymap, area: Rect) {
+    if let Some(command) = &mut app.command {
+        let (visible, cursor) = command.input.view(area.width.saturating_sub(1) as usize);
+        app.command_cursor_x = area.left() + cursor as u16 + 1;
+        app.command_cursor_y = area.top();
+        let mut text = vec![Text::raw(format!(":{}", visible))];
+        if let Some(err) = &command.error {
+            text.push(Text::styled(
+                format!("   {}", err),
+                Style::default().fg(Color::Red),
+            ));
+        }
+        let paragraph = Paragraph::new(text.iter()).wrap(false);
+        f.render_widget(paragraph, area);
+        return;
+    }
+    let text = match &app.loading {
+        Some((_, loading)) => {
+            let spinner = SPINNER[app.spinner % SPINNER.len()];
+            let message = match loading {
+                Loading::Search { search, .. } => format!("Searching \"{}\"", search),
+                Loading::More { search, .. } => format!("Loading more results for \"{}\"", search),
+                Loading::Page { title, lang, .. } => format!("Loading \"{}\" ({})", title, lang),
+                Loading::Random => "Picking a random article".to_owned(),
+            };
+            let cancel = key_names(keymap, app.mode(), &[Action::Escape]);
+            let hint = if cancel.is_empty() {
+                String::new()
+            } else {
+                format!(" ({} to cancel)", cancel.join(", "))
+            };
+            [Text::styled(
+                format!("{} {}...{}", spinner, message, hint),
+                Style::default().fg(Color::Green),
+            )]
+        }
+        None => match &app.find {
+            Some(find) => {
+                let mut flags = String::new();
+                if find.case_sensitive {
+                    flags.push_str(" [case]");
+                }
+                if find.regex {
+                    flags.push_str(" [regex]");
+                }
+                let result = if let Some(err) = &find.error {
+                    err.lines().last().unwrap_or_default().to_owned()
+                } else if find.query.is_empty() {
+                    let mode = app.mode();
+                    [
+                        (Action::FindCase, "for case sensitive"),
+                        (Action::FindRegex, "for regex"),
+                    ]
+                    .iter()
+                    .map(|(action, what)| (key_names(keymap, mode, &[*action]), what))
+                    .filter(|(keys, _)| !keys.is_empty())
+                    .map(|(keys, what)| format!("{} {}", keys.join(", "), what))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+                } else if find.count == 0 {
+                    "no matches".to_owned()
+                } else {
+                    format!("match {}/{}", find.current + 1, find.count)
+                };
+                [Text::styled(
+                    format!("/{}{}   {}", find.query, flags, result),
+                    Style::default().fg(if find.error.is_some() {
+                        Color::Red
+                    } else {
+                        Color::Cyan
+                    }),
+                )]
+            }
+            None => [Text::styled(
+                &app.status,
+                Style::default().fg(Color::Yellow),
+            )],
+        },
+    };
+    let paragraph = Paragraph::new(text.iter()).wrap(false);
+    f.render_widget(paragraph, area);
+}
+
+fn draw_error<B: Backend>(f: &mut Frame<B>, app: &mut App, keymap: &Keymap, area: Rect) {
+    let message = match &app.error {
+        Some(err) => err.to_string(),
+        None => return,
+    };
+    let dismiss = key_names(keymap, app.mode(), &[Action::Escape, Action::Enter]);
+    let hint = if dismiss.is_empty() {
+        String::new()
+    } else {
+        format!("Press {} to dismiss.", dismiss.join(" or "))
+    };
+    let text = [
+        Text::raw(format!("{}\n\n", message)),
+        Text::styled(hint, Style::default().modifier(Modifier::DIM)),
+    ];
+    let paragraph = Paragraph::new(text.iter())
+        .block(
+            Block::default()
+                .title("Error")
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(Color::Red)),
+        )
+        .alignment(Alignment::Left)
+        .wrap(true);
+    let area = centered_rect(60, 7, area);
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+fn draw_picker<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
+    let picker = match &mut app.picker {
+        Some(picker) => picker,
+        None => return,
+    };
+    let items = picker.list.items.iter().map(Text::raw);
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(&picker.title)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(Color::Green)),
+        )
+        .highlight_style(Style::default().fg(Color::Green).modifier(Modifier::BOLD))
+        .highlight_symbol("> ");
+    let area = centered_rect(60, picker.list.items.len() as u16 + 2, area);
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, area, &mut picker.list.state);
+}
+
+/// Split `ranges` so that `start..end` has `style` on top of its own.
+fn overlay(
+    ranges: Vec<(usize, usize, Style)>,
+    start: usize,
+    end: usize,
+    style: Style,
+) -> Vec<(usize, usize, Style)> {
+    let mut split = Vec::new();
+    for (s, e, base) in ranges {
+        if e <= start || s >= end {
+            split.push((s, e, base));
+            continue;
+        }
+        if s < start {
+            split.push((s, start, base));
+        }
+        split.push((s.max(start), e.min(end), patch(base, style)));
+        if e > end {
+            split.push((end, e, base));
+        }
+    }
+    split
+}
+
+/// Border of a panel, highlighted when it has focus
+fn border_style(focused: bool, modifier: Modifier) -> Style {
+    if focused {
+        Style::default().fg(Color::Green)
+    } else {
+        Style::default().modifier(modifier)
+    }
+}
+
+/// A rectangle of `percent_x` of the width and `height` rows in the middle of
+/// `area`.
+fn centered_rect(percent_x: u16, height: u16, area: Rect) -> Rect {
+    let width = (u32::from(area.width) * u32::from(percent_x.min(100)) / 100) as u16;
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
@@ -0,0 +1,54 @@
+use std::time::{Duration, Instant};
+
+/// Fires once a burst of calls to `poke` has been quiet for `delay`
+pub struct Debounce {
+    delay: Duration,
+    due: Option<Instant>,
+}
+
+impl Debounce {
+    pub fn new(delay: Duration) -> Self {
+        Self { delay, due: None }
+    }
+
+    /// Push the deadline back to `delay` after `now`.
+    pub fn poke(&mut self, now: Instant) {
+        self.due = Some(now + self.delay);
+    }
+
+    pub fn cancel(&mut self) {
+        self.due = None;
+    }
+
+    /// Whether the deadline has passed, at most once per burst.
+    pub fn ready(&mut self, now: Instant) -> bool {
+        match self.due {
+            Some(due) if now >= due => {
+                self.due = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debounce_fires_once_after_burst() {
+        let start = Instant::now();
+        let ms = Duration::from_millis;
+        let mut debounce = Debounce::new(ms(300));
+        assert!(!debounce.ready(start));
+        debounce.poke(start);
+        debounce.poke(start + ms(200));
+        assert!(!debounce.ready(start + ms(400)));
+        assert!(debounce.ready(start + ms(500)));
+        assert!(!debounce.ready(start + ms(600)));
+        debounce.poke(start + ms(600));
+        debounce.cancel();
+        assert!(!debounce.ready(start + ms(1000)));
+    }
+}
@@ -1,19 +1,24 @@
+use crate::request::{Error, Request};
 use crate::util::key::Key;
-use crossterm::event;
+use crossterm::event::{self, MouseEvent};
 use std::sync::mpsc;
 use std::thread;
 use std::time::Duration;
 
 pub enum Event<I> {
     Input(I),
+    /// Clicks and scrolling, with the terminal cell they happened on
+    Mouse(MouseEvent),
     Tick,
+    /// Response to a request made through `request::Worker`
+    Response(u64, Result<Box<Request>, Error>),
 }
 
 /// A small event handler that wrap crossterm input and tick events. Each event
 /// type is handled in its own thread and returned to a common `Receiver`
 pub struct Events {
     rx: mpsc::Receiver<Event<Key>>,
-    _tx: mpsc::Sender<Event<Key>>,
+    tx: mpsc::Sender<Event<Key>>,
 }
 
 impl Events {
@@ -22,14 +27,20 @@ impl Events {
         let event_tx = tx.clone();
         thread::spawn(move || loop {
             if event::poll(Duration::from_millis(100)).unwrap() {
-                if let event::Event::Key(event) = event::read().unwrap() {
-                    let key = Key::from(event);
-                    tx.send(Event::Input(key)).unwrap();
+                match event::read().unwrap() {
+                    event::Event::Key(event) => tx.send(Event::Input(Key::from(event))).unwrap(),
+                    event::Event::Mouse(event) => tx.send(Event::Mouse(event)).unwrap(),
+                    event::Event::Resize(..) => {}
                 }
             }
             tx.send(Event::Tick).unwrap();
         });
-        Events { rx, _tx: event_tx }
+        Events { rx, tx: event_tx }
+    }
+
+    /// Sender for events produced outside of the input thread
+    pub fn sender(&self) -> mpsc::Sender<Event<Key>> {
+        self.tx.clone()
     }
 
     pub fn next(&self) -> Result<Event<Key>, mpsc::RecvError> {
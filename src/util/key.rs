@@ -1,5 +1,8 @@
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::fmt;
+use std::str::FromStr;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Key {
     Backspace,
     Enter,
@@ -8,6 +11,13 @@ pub enum Key {
     Up,
     Down,
     Tab,
+    BackTab,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    CtrlLeft,
+    CtrlRight,
     Delete,
     Insert,
     F(u8),
@@ -34,6 +44,14 @@ impl From<KeyEvent> for Key {
                 code: KeyCode::Char(char),
                 modifiers: KeyModifiers::CONTROL
             } => Key::Ctrl(char),
+            KeyEvent {
+                code: KeyCode::Left,
+                modifiers: KeyModifiers::CONTROL
+            } => Key::CtrlLeft,
+            KeyEvent {
+                code: KeyCode::Right,
+                modifiers: KeyModifiers::CONTROL
+            } => Key::CtrlRight,
             KeyEvent { code: KeyCode::Backspace,  .. } => Key::Backspace,
             KeyEvent { code: KeyCode::Enter,      .. } => Key::Enter,
             KeyEvent { code: KeyCode::Left,       .. } => Key::Left,
@@ -41,6 +59,11 @@ impl From<KeyEvent> for Key {
             KeyEvent { code: KeyCode::Up,         .. } => Key::Up,
             KeyEvent { code: KeyCode::Down,       .. } => Key::Down,
             KeyEvent { code: KeyCode::Tab,        .. } => Key::Tab,
+            KeyEvent { code: KeyCode::BackTab,    .. } => Key::BackTab,
+            KeyEvent { code: KeyCode::PageUp,     .. } => Key::PageUp,
+            KeyEvent { code: KeyCode::PageDown,   .. } => Key::PageDown,
+            KeyEvent { code: KeyCode::Home,       .. } => Key::Home,
+            KeyEvent { code: KeyCode::End,        .. } => Key::End,
             KeyEvent { code: KeyCode::Delete,     .. } => Key::Delete,
             KeyEvent { code: KeyCode::Insert,     .. } => Key::Insert,
             KeyEvent { code: KeyCode::F(u8),      .. } => Key::F(u8),
@@ -50,3 +73,91 @@ impl From<KeyEvent> for Key {
         }
     }
 }
+
+/// Parse a key chord as written in the keymap file, e.g. `q`, `ctrl-r`,
+/// `alt-c`, `pagedown` or `f5`.
+impl FromStr for Key {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Ok(Key::Char(c));
+        }
+        let single = |rest: &str| {
+            let mut chars = rest.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Some(c),
+                _ => None,
+            }
+        };
+        let lower = s.to_lowercase();
+        let key = match lower.as_str() {
+            "backspace" => Key::Backspace,
+            "enter" => Key::Enter,
+            "left" => Key::Left,
+            "right" => Key::Right,
+            "up" => Key::Up,
+            "down" => Key::Down,
+            "tab" => Key::Tab,
+            "backtab" | "shift-tab" => Key::BackTab,
+            "pageup" => Key::PageUp,
+            "pagedown" => Key::PageDown,
+            "home" => Key::Home,
+            "end" => Key::End,
+            "ctrl-left" => Key::CtrlLeft,
+            "ctrl-right" => Key::CtrlRight,
+            "delete" => Key::Delete,
+            "insert" => Key::Insert,
+            "esc" => Key::Esc,
+            "space" => Key::Char(' '),
+            _ => {
+                if let Some(c) = lower.strip_prefix("ctrl-").and_then(single) {
+                    Key::Ctrl(c)
+                } else if let Some(c) = s
+                    .get(4..)
+                    .filter(|_| lower.starts_with("alt-"))
+                    .and_then(single)
+                {
+                    Key::Alt(c)
+                } else if let Some(n) = lower.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Key::F(n)
+                } else {
+                    return Err(format!("Unknown key '{}'", s));
+                }
+            }
+        };
+        Ok(key)
+    }
+}
+
+/// Name a key the way the help row shows it, e.g. `q`, `Ctrl-R` or `PageDown`.
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Key::Backspace => write!(f, "Backspace"),
+            Key::Enter => write!(f, "Enter"),
+            Key::Left => write!(f, "Left"),
+            Key::Right => write!(f, "Right"),
+            Key::Up => write!(f, "Up"),
+            Key::Down => write!(f, "Down"),
+            Key::Tab => write!(f, "Tab"),
+            Key::BackTab => write!(f, "Shift-Tab"),
+            Key::PageUp => write!(f, "PageUp"),
+            Key::PageDown => write!(f, "PageDown"),
+            Key::Home => write!(f, "Home"),
+            Key::End => write!(f, "End"),
+            Key::CtrlLeft => write!(f, "Ctrl-Left"),
+            Key::CtrlRight => write!(f, "Ctrl-Right"),
+            Key::Delete => write!(f, "Delete"),
+            Key::Insert => write!(f, "Insert"),
+            Key::F(n) => write!(f, "F{}", n),
+            Key::Alt(c) => write!(f, "Alt-{}", c.to_uppercase()),
+            Key::Ctrl(c) => write!(f, "Ctrl-{}", c.to_uppercase()),
+            Key::Char(' ') => write!(f, "Space"),
+            Key::Char(c) => write!(f, "{}", c),
+            Key::Esc => write!(f, "Esc"),
+            Key::Unused => write!(f, "?"),
+        }
+    }
+}
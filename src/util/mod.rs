@@ -1,7 +1,9 @@
+mod debounce;
 mod event;
 mod key;
 mod state;
 
+pub use debounce::Debounce;
 pub use event::{Event, Events};
 pub use key::Key;
 pub use state::StatefulList;
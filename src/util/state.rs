@@ -3,6 +3,8 @@ use tui::widgets::ListState;
 pub struct StatefulList<T> {
     pub state: ListState,
     pub items: Vec<T>,
+    /// First item on screen, for lists drawn without `tui::widgets::List`
+    pub offset: usize,
 }
 
 impl<T> Default for StatefulList<T> {
@@ -10,6 +12,7 @@ impl<T> Default for StatefulList<T> {
         Self {
             state: ListState::default(),
             items: Vec::new(),
+            offset: 0,
         }
     }
 }
@@ -19,16 +22,21 @@ impl<T> StatefulList<T> {
         StatefulList {
             state: ListState::default(),
             items,
+            offset: 0,
         }
     }
 
     pub fn next(&mut self, increment: usize) {
+        if self.items.is_empty() {
+            return;
+        }
+        let last = self.items.len() - 1;
         let i = match self.state.selected() {
             Some(i) => {
-                if i >= self.items.len() - increment {
+                if i >= last {
                     0
                 } else {
-                    i + increment
+                    (i + increment).min(last)
                 }
             }
             None => 0,
@@ -37,12 +45,15 @@ impl<T> StatefulList<T> {
     }
 
     pub fn previous(&mut self, increment: usize) {
+        if self.items.is_empty() {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
                 if i == 0 {
-                    self.items.len() - increment
+                    self.items.len() - 1
                 } else {
-                    i - increment
+                    i.saturating_sub(increment)
                 }
             }
             None => 0,
@@ -50,6 +61,19 @@ impl<T> StatefulList<T> {
         self.state.select(Some(i));
     }
 
+    /// Move the selection by `increment` without wrapping around.
+    pub fn shift(&mut self, forward: bool, increment: usize) {
+        if self.items.is_empty() {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) if forward => (i + increment).min(self.items.len() - 1),
+            Some(i) => i.saturating_sub(increment),
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
+
     // pub fn unselect(&mut self) {
     //     self.state.select(None);
     // }
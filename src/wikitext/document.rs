@@ -0,0 +1,180 @@
+/// A parsed wikitext article: a flat sequence of block level elements.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Document {
+    pub blocks: Vec<Block>,
+}
+
+impl Document {
+    /// Entries of the lists on a disambiguation page: the first link of each
+    /// item as `(title, description)`, the description being the rest of the
+    /// item.
+    pub fn link_list(&self) -> Vec<(String, String)> {
+        let mut entries = Vec::new();
+        for block in &self.blocks {
+            let items = match block {
+                Block::List(items) => items,
+                _ => continue,
+            };
+            for item in items {
+                let (target, label) = match first_link(&item.content) {
+                    Some(link) => link,
+                    None => continue,
+                };
+                let title = target.split('#').next().unwrap_or_default().trim();
+                if title.is_empty() {
+                    continue;
+                }
+                let label = text(label);
+                let item_text = text(&item.content);
+                let description = item_text
+                    .trim()
+                    .strip_prefix(label.trim())
+                    .unwrap_or(&item_text)
+                    .trim_start_matches(|c: char| c == ',' || c.is_whitespace())
+                    .trim_end();
+                entries.push((title.to_owned(), description.to_owned()));
+            }
+        }
+        entries
+    }
+}
+
+fn first_link(content: &[Inline]) -> Option<(&str, &[Inline])> {
+    content.iter().find_map(|inline| match inline {
+        Inline::Link { target, label } => Some((target.as_str(), label.as_slice())),
+        Inline::Bold(content) | Inline::Italic(content) => first_link(content),
+        _ => None,
+    })
+}
+
+/// Plain text of `content`, leaving out refs and templates.
+pub fn text(content: &[Inline]) -> String {
+    let mut text = String::new();
+    for inline in content {
+        match inline {
+            Inline::Text(t) => text.push_str(t),
+            Inline::Bold(content) | Inline::Italic(content) => text.push_str(&self::text(content)),
+            Inline::Link { label, .. } => text.push_str(&self::text(label)),
+            Inline::ExternalLink { url, label } => {
+                if label.is_empty() {
+                    text.push_str(url);
+                } else {
+                    text.push_str(&self::text(label));
+                }
+            }
+            Inline::LineBreak => text.push(' '),
+            Inline::Ref { .. } | Inline::Template(_) => {}
+        }
+    }
+    text
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Block {
+    /// `== Heading ==`, `level` is the number of `=` (2 for top level sections)
+    Heading {
+        level: u8,
+        content: Vec<Inline>,
+    },
+    Paragraph(Vec<Inline>),
+    /// Consecutive `*`, `#`, `;` and `:` lines
+    List(Vec<ListItem>),
+    /// `{| ... |}`
+    Table(Table),
+    /// `<blockquote> ... </blockquote>`
+    Quote(Vec<Block>),
+    /// A template standing on its own line, e.g. an infobox or a hatnote
+    Template(Template),
+    /// `----`
+    Rule,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListItem {
+    /// Number of prefix characters, starting at 1
+    pub depth: usize,
+    pub kind: ListKind,
+    pub content: Vec<Inline>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ListKind {
+    Bullet,
+    Numbered,
+    Term,
+    Definition,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Table {
+    pub caption: Option<Vec<Inline>>,
+    pub rows: Vec<Vec<Cell>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cell {
+    pub header: bool,
+    pub content: Vec<Inline>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    pub name: String,
+    /// Raw arguments, named ones are kept as `key=value`
+    pub args: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inline {
+    Text(String),
+    Bold(Vec<Inline>),
+    Italic(Vec<Inline>),
+    /// `[[target|label]]`
+    Link {
+        target: String,
+        label: Vec<Inline>,
+    },
+    /// `[url label]` or a bare url
+    ExternalLink {
+        url: String,
+        label: Vec<Inline>,
+    },
+    /// `<ref>...</ref>` or `<ref name="..." />`
+    Ref {
+        name: Option<String>,
+        content: Vec<Inline>,
+    },
+    Template(Template),
+    /// `<br>`
+    LineBreak,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::wikitext::parse;
+
+    #[test]
+    fn link_list_of_disambiguation() {
+        let document = parse(
+            "'''Mercury''' may refer to:\n\
+             == Science ==\n\
+             * [[Mercury (planet)]], the closest planet to the Sun\n\
+             * ''[[Mercury (element)|Mercury]]'', a chemical element\n\
+             * A plain entry\n\
+             {{disambiguation}}",
+        );
+        assert_eq!(
+            document.link_list(),
+            vec![
+                (
+                    "Mercury (planet)".to_owned(),
+                    "the closest planet to the Sun".to_owned()
+                ),
+                (
+                    "Mercury (element)".to_owned(),
+                    "a chemical element".to_owned()
+                ),
+            ]
+        );
+    }
+}
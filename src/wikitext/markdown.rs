@@ -0,0 +1,139 @@
+use crate::wikitext::document::{Block, Cell, Document, Inline, ListKind};
+
+/// Write `document` as Markdown under a `# title` heading. Links point at
+/// `base`, e.g. `https://en.wikipedia.org`, refs and templates are left out.
+pub fn to_markdown(title: &str, document: &Document, base: &str) -> String {
+    let mut blocks = vec![format!("# {}", title)];
+    blocks.extend(
+        document
+            .blocks
+            .iter()
+            .map(|block| block_markdown(block, base))
+            .filter(|block| !block.is_empty()),
+    );
+    let mut markdown = blocks.join("\n\n");
+    markdown.push('\n');
+    markdown
+}
+
+fn block_markdown(block: &Block, base: &str) -> String {
+    match block {
+        Block::Heading { level, content } => format!(
+            "{} {}",
+            "#".repeat((*level as usize).clamp(2, 6)),
+            inline_markdown(content, base).trim()
+        ),
+        Block::Paragraph(content) => inline_markdown(content, base).trim().to_owned(),
+        Block::List(items) => items
+            .iter()
+            .map(|item| {
+                let indent = "  ".repeat(item.depth.saturating_sub(1));
+                let content = inline_markdown(&item.content, base);
+                match item.kind {
+                    ListKind::Bullet => format!("{}- {}", indent, content.trim()),
+                    ListKind::Numbered => format!("{}1. {}", indent, content.trim()),
+                    ListKind::Term => format!("{}**{}**", indent, content.trim()),
+                    ListKind::Definition => format!("{}: {}", indent, content.trim()),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Block::Table(table) => {
+            let columns = table.rows.iter().map(Vec::len).max().unwrap_or(0);
+            if columns == 0 {
+                return String::new();
+            }
+            let row = |cells: &[Cell]| {
+                let mut cells = cells
+                    .iter()
+                    .map(|cell| {
+                        inline_markdown(&cell.content, base)
+                            .trim()
+                            .replace('|', "\\|")
+                    })
+                    .collect::<Vec<_>>();
+                cells.resize(columns, String::new());
+                format!("| {} |", cells.join(" | "))
+            };
+            let mut lines = Vec::new();
+            if let Some(caption) = &table.caption {
+                lines.push(format!("*{}*", inline_markdown(caption, base).trim()));
+                lines.push(String::new());
+            }
+            lines.push(row(&table.rows[0]));
+            lines.push(format!("|{}", " --- |".repeat(columns)));
+            lines.extend(table.rows[1..].iter().map(|cells| row(cells)));
+            lines.join("\n")
+        }
+        Block::Quote(blocks) => blocks
+            .iter()
+            .map(|block| block_markdown(block, base))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+            .lines()
+            .map(|line| format!("> {}", line).trim_end().to_owned())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Block::Template(_) => String::new(),
+        Block::Rule => "---".to_owned(),
+    }
+}
+
+fn inline_markdown(content: &[Inline], base: &str) -> String {
+    let mut markdown = String::new();
+    for inline in content {
+        match inline {
+            Inline::Text(text) => markdown.push_str(text),
+            Inline::Bold(content) => {
+                markdown.push_str(&format!("**{}**", inline_markdown(content, base)))
+            }
+            Inline::Italic(content) => {
+                markdown.push_str(&format!("*{}*", inline_markdown(content, base)))
+            }
+            Inline::Link { target, label } => {
+                let target = target.trim().trim_start_matches(':').replace(' ', "_");
+                markdown.push_str(&format!(
+                    "[{}]({}/wiki/{})",
+                    inline_markdown(label, base),
+                    base,
+                    target
+                ));
+            }
+            Inline::ExternalLink { url, label } => {
+                if label.is_empty() {
+                    markdown.push_str(&format!("<{}>", url));
+                } else {
+                    markdown.push_str(&format!("[{}]({})", inline_markdown(label, base), url));
+                }
+            }
+            Inline::LineBreak => markdown.push_str("<br>"),
+            Inline::Ref { .. } | Inline::Template(_) => {}
+        }
+    }
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wikitext::parse;
+
+    #[test]
+    fn markdown_of_document() {
+        let document = parse(
+            "'''Rust''' is an [[iron oxide|oxide]].<ref>Chemistry</ref>\n\
+             == Uses ==\n\
+             * ''Paint''\n\
+             ** [https://example.com Primer]\n\
+             {|\n! Name !! Formula\n|-\n| Rust || Fe2O3\n|}",
+        );
+        assert_eq!(
+            to_markdown("Rust", &document, "https://en.wikipedia.org"),
+            "# Rust\n\n\
+             **Rust** is an [oxide](https://en.wikipedia.org/wiki/iron_oxide).\n\n\
+             ## Uses\n\n\
+             - *Paint*\n  - [Primer](https://example.com)\n\n\
+             | Name | Formula |\n| --- | --- |\n| Rust | Fe2O3 |\n"
+        );
+    }
+}
@@ -0,0 +1,7 @@
+mod document;
+mod markdown;
+mod parser;
+
+pub use document::{Block, Document, Inline, ListKind, Table};
+pub use markdown::to_markdown;
+pub use parser::{decode_entities, parse};
@@ -0,0 +1,838 @@
+use crate::wikitext::document::{
+    Block, Cell, Document, Inline, ListItem, ListKind, Table, Template,
+};
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    static ref COMMENT: Regex = Regex::new(r"(?s)<!--.*?(-->|$)").unwrap();
+    static ref MAGIC_WORD: Regex = Regex::new(r"__[A-Z]+__").unwrap();
+    static ref HEADING: Regex = Regex::new(r"^(={1,6})\s*(.+?)\s*={1,6}\s*$").unwrap();
+    static ref TAG: Regex = Regex::new(r"^<(/?)([a-zA-Z]+)([^<>]*?)(/?)>").unwrap();
+    static ref REF_NAME: Regex = Regex::new(r#"name\s*=\s*"?([^"/>]+?)"?\s*(/|$)"#).unwrap();
+    static ref ENTITY: Regex = Regex::new(r"^&(#[0-9]+|#[xX][0-9a-fA-F]+|[a-zA-Z]+);").unwrap();
+}
+
+/// Tags whose content is shown verbatim
+const LITERAL_TAGS: [&str; 6] = ["nowiki", "math", "chem", "pre", "source", "syntaxhighlight"];
+/// Tags whose content has no place in a terminal
+const HIDDEN_TAGS: [&str; 9] = [
+    "gallery",
+    "references",
+    "templatestyles",
+    "timeline",
+    "imagemap",
+    "score",
+    "graph",
+    "mapframe",
+    "indicator",
+];
+/// Link namespaces that are not rendered as links
+const HIDDEN_NAMESPACES: [&str; 4] = ["file:", "image:", "category:", "media:"];
+
+/// Parse an article's wikitext into a `Document`.
+pub fn parse(wikitext: &str) -> Document {
+    let source = COMMENT.replace_all(wikitext, "");
+    let source = MAGIC_WORD.replace_all(&source, "");
+    Document {
+        blocks: blocks(&source),
+    }
+}
+
+fn blocks(src: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut paragraph = Inlines::default();
+    let mut list = Vec::new();
+    let mut pos = 0;
+    while pos < src.len() {
+        let end = line_end(src, pos);
+        let next = (end + 1).min(src.len());
+        let line = &src[pos..end];
+        let trimmed = line.trim();
+
+        if !list.is_empty() && !is_list_item(line) {
+            blocks.push(Block::List(std::mem::take(&mut list)));
+        }
+
+        if trimmed.is_empty() {
+            paragraph.flush_into(&mut blocks);
+        } else if let Some(caps) = HEADING.captures(line) {
+            paragraph.flush_into(&mut blocks);
+            blocks.push(Block::Heading {
+                level: caps[1].len() as u8,
+                content: inline(&caps[2]),
+            });
+        } else if line.starts_with("{|") {
+            paragraph.flush_into(&mut blocks);
+            let end = table_end(src, pos);
+            blocks.push(Block::Table(table(&src[pos..end])));
+            pos = end;
+            continue;
+        } else if line.starts_with("----") {
+            paragraph.flush_into(&mut blocks);
+            blocks.push(Block::Rule);
+        } else if is_list_item(line) {
+            paragraph.flush_into(&mut blocks);
+            list.push(list_item(line));
+        } else if starts_with_ignore_case(trimmed, "<blockquote") {
+            paragraph.flush_into(&mut blocks);
+            let start = pos + line.find('>').map_or(line.len(), |i| i + 1);
+            let (inner, after) = match find_ignore_case(&src[start..], "</blockquote>") {
+                Some(i) => (&src[start..start + i], start + i + "</blockquote>".len()),
+                None => (&src[start..], src.len()),
+            };
+            blocks.push(Block::Quote(self::blocks(inner)));
+            pos = after;
+            continue;
+        } else {
+            let content = inline(line);
+            if content.iter().all(is_blank) {
+                // A line made of templates and hidden markup only (infoboxes,
+                // hatnotes, files, categories) is not part of any paragraph
+                for each in content {
+                    if let Inline::Template(template) = each {
+                        paragraph.flush_into(&mut blocks);
+                        blocks.push(Block::Template(template));
+                    }
+                }
+            } else {
+                if !paragraph.is_empty() {
+                    paragraph.push(Inline::Text(" ".to_owned()));
+                }
+                paragraph.extend(content);
+            }
+        }
+        pos = next;
+    }
+    if !list.is_empty() {
+        blocks.push(Block::List(list));
+    }
+    paragraph.flush_into(&mut blocks);
+    blocks
+}
+
+fn is_blank(inline: &Inline) -> bool {
+    match inline {
+        Inline::Text(text) => text.trim().is_empty(),
+        Inline::Template(_) => true,
+        _ => false,
+    }
+}
+
+fn is_list_item(line: &str) -> bool {
+    line.starts_with(|c| "*#:;".contains(c))
+}
+
+fn list_item(line: &str) -> ListItem {
+    let prefix = line
+        .chars()
+        .take_while(|c| "*#:;".contains(*c))
+        .collect::<String>();
+    let kind = match prefix.chars().last() {
+        Some('#') => ListKind::Numbered,
+        Some(';') => ListKind::Term,
+        Some(':') => ListKind::Definition,
+        _ => ListKind::Bullet,
+    };
+    ListItem {
+        depth: prefix.len(),
+        kind,
+        content: trim(inline(&line[prefix.len()..])),
+    }
+}
+
+/// End of the logical line starting at `pos`. Templates, links and refs may
+/// span several physical lines.
+fn line_end(src: &str, pos: usize) -> usize {
+    let bytes = src.as_bytes();
+    let mut i = pos;
+    while i < bytes.len() {
+        let rest = &bytes[i..];
+        if rest[0] == b'\n' {
+            return i;
+        } else if rest.starts_with(b"{{") {
+            i = find_closing(src, i + 2, "{{", "}}").map_or(i + 2, |end| end + 2);
+        } else if rest.starts_with(b"[[") {
+            i = find_closing(src, i + 2, "[[", "]]").map_or(i + 2, |end| end + 2);
+        } else if rest[0] == b'<'
+            && starts_with_ignore_case(&src[i..], "<ref")
+            && !self_closing(&src[i..])
+        {
+            i = find_ignore_case(&src[i..], "</ref>").map_or(i + 4, |end| i + end + 6);
+        } else {
+            i += 1;
+        }
+    }
+    bytes.len()
+}
+
+/// Index of the `close` token balancing an already consumed `open` token.
+fn find_closing(src: &str, from: usize, open: &str, close: &str) -> Option<usize> {
+    let bytes = src.as_bytes();
+    let mut depth = 1;
+    let mut i = from;
+    while i < bytes.len() {
+        if bytes[i..].starts_with(open.as_bytes()) {
+            depth += 1;
+            i += open.len();
+        } else if bytes[i..].starts_with(close.as_bytes()) {
+            depth -= 1;
+            if depth == 0 {
+                return Some(i);
+            }
+            i += close.len();
+        } else {
+            i += 1;
+        }
+    }
+    None
+}
+
+/// Split on `sep` outside of nested links and templates.
+fn split_top_level<'a>(src: &'a str, sep: &str) -> Vec<&'a str> {
+    let bytes = src.as_bytes();
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let rest = &bytes[i..];
+        if rest.starts_with(b"[[") || rest.starts_with(b"{{") {
+            depth += 1;
+            i += 2;
+        } else if depth > 0 && (rest.starts_with(b"]]") || rest.starts_with(b"}}")) {
+            depth -= 1;
+            i += 2;
+        } else if depth == 0 && rest.starts_with(sep.as_bytes()) {
+            parts.push(&src[start..i]);
+            i += sep.len();
+            start = i;
+        } else {
+            i += 1;
+        }
+    }
+    parts.push(&src[start..]);
+    parts
+}
+
+fn starts_with_ignore_case(src: &str, prefix: &str) -> bool {
+    src.len() >= prefix.len()
+        && src.as_bytes()[..prefix.len()].eq_ignore_ascii_case(prefix.as_bytes())
+}
+
+/// Byte offset of ASCII `needle` in `src`, ignoring case.
+fn find_ignore_case(src: &str, needle: &str) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(0);
+    }
+    src.as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+fn self_closing(tag: &str) -> bool {
+    tag.find('>').is_some_and(|end| tag[..end].ends_with('/'))
+}
+
+/// End of the table starting at `pos`, including nested tables.
+fn table_end(src: &str, pos: usize) -> usize {
+    let mut depth = 0;
+    let mut i = pos;
+    for line in src[pos..].split_inclusive('\n') {
+        i += line.len();
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("{|") {
+            depth += 1;
+        } else if trimmed.starts_with("|}") {
+            depth -= 1;
+            if depth == 0 {
+                return i;
+            }
+        }
+    }
+    src.len()
+}
+
+fn table(src: &str) -> Table {
+    let mut table = Table::default();
+    let mut cells: Vec<(bool, String)> = Vec::new();
+    let mut nested = 0;
+    for line in src.lines().skip(1) {
+        let trimmed = line.trim_start();
+        if nested > 0 {
+            if trimmed.starts_with("{|") {
+                nested += 1;
+            } else if trimmed.starts_with("|}") {
+                nested -= 1;
+            }
+        } else if trimmed.starts_with("{|") {
+            // Nested tables are not rendered
+            nested = 1;
+        } else if trimmed.starts_with("|}") {
+            break;
+        } else if let Some(caption) = trimmed.strip_prefix("|+") {
+            table.caption = Some(trim(inline(cell_content(caption))));
+        } else if trimmed.starts_with("|-") {
+            push_row(&mut table, &mut cells);
+        } else if let Some(rest) = trimmed.strip_prefix('!') {
+            for each in split_top_level(rest, "!!") {
+                for cell in split_top_level(each, "||") {
+                    cells.push((true, cell.to_owned()));
+                }
+            }
+        } else if let Some(rest) = trimmed.strip_prefix('|') {
+            for cell in split_top_level(rest, "||") {
+                cells.push((false, cell.to_owned()));
+            }
+        } else if let Some((_, cell)) = cells.last_mut() {
+            cell.push('\n');
+            cell.push_str(line);
+        }
+    }
+    push_row(&mut table, &mut cells);
+    table
+}
+
+fn push_row(table: &mut Table, cells: &mut Vec<(bool, String)>) {
+    if cells.is_empty() {
+        return;
+    }
+    let row = cells
+        .drain(..)
+        .map(|(header, cell)| Cell {
+            header,
+            content: trim(inline(cell_content(&cell))),
+        })
+        .collect();
+    table.rows.push(row);
+}
+
+/// Strip the `attr="..." |` prefix of a table cell.
+fn cell_content(cell: &str) -> &str {
+    let parts = split_top_level(cell, "|");
+    if parts.len() > 1 && parts[0].contains('=') {
+        &cell[parts[0].len() + 1..]
+    } else {
+        cell
+    }
+}
+
+/// Parse a run of inline wikitext. Newlines are treated as spaces.
+fn inline(src: &str) -> Vec<Inline> {
+    let mut inlines = Inlines::default();
+    let bytes = src.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let rest = &src[i..];
+        if rest.starts_with("''") {
+            let count = rest.bytes().take_while(|b| *b == b'\'').count();
+            let (literal, emphasis) = match count {
+                2 => (0, vec![Emphasis::Italic]),
+                3 => (0, vec![Emphasis::Bold]),
+                4 => (1, vec![Emphasis::Bold]),
+                _ => (count - 5, vec![Emphasis::Italic, Emphasis::Bold]),
+            };
+            inlines.push_str(&rest[..literal]);
+            for each in emphasis {
+                inlines.toggle(each);
+            }
+            i += count;
+        } else if rest.starts_with("[[") {
+            match find_closing(src, i + 2, "[[", "]]") {
+                Some(end) => {
+                    let trail = src[end + 2..]
+                        .bytes()
+                        .take_while(|b| b.is_ascii_lowercase())
+                        .count();
+                    if let Some(link) = link(&src[i + 2..end], &src[end + 2..end + 2 + trail]) {
+                        inlines.push(link);
+                    }
+                    i = end + 2 + trail;
+                }
+                None => {
+                    inlines.push_str("[[");
+                    i += 2;
+                }
+            }
+        } else if rest.starts_with('[') && is_url(&rest[1..]) && rest.contains(']') {
+            let end = i + rest.find(']').unwrap();
+            let inner = src[i + 1..end].trim();
+            let (url, label) = match inner.find(char::is_whitespace) {
+                Some(space) => (&inner[..space], trim(inline(&inner[space..]))),
+                None => (inner, Vec::new()),
+            };
+            inlines.push(Inline::ExternalLink {
+                url: url.to_owned(),
+                label,
+            });
+            i = end + 1;
+        } else if (rest.starts_with("http://") || rest.starts_with("https://"))
+            && !src[..i].ends_with(|c: char| c.is_alphanumeric())
+        {
+            let len = rest
+                .find(|c: char| c.is_whitespace() || "[]<>\"{}|".contains(c))
+                .unwrap_or(rest.len());
+            let url = rest[..len].trim_end_matches(|c| ".,;:!?)".contains(c));
+            inlines.push(Inline::ExternalLink {
+                url: url.to_owned(),
+                label: Vec::new(),
+            });
+            i += url.len();
+        } else if rest.starts_with("{{") {
+            match find_closing(src, i + 2, "{{", "}}") {
+                Some(end) => {
+                    inlines.push(Inline::Template(template(&src[i + 2..end])));
+                    i = end + 2;
+                }
+                None => {
+                    inlines.push_str("{{");
+                    i += 2;
+                }
+            }
+        } else if let Some(caps) = TAG.captures(rest) {
+            i += tag(&caps, rest, &mut inlines);
+        } else if let Some(caps) = ENTITY.captures(rest) {
+            match entity(&caps[1]) {
+                Some(c) => inlines.push_str(c.encode_utf8(&mut [0; 4])),
+                None => inlines.push_str(&caps[0]),
+            }
+            i += caps[0].len();
+        } else {
+            let c = rest.chars().next().unwrap();
+            if c == '\n' {
+                inlines.push_str(" ");
+            } else {
+                inlines.push_str(c.encode_utf8(&mut [0; 4]));
+            }
+            i += c.len_utf8();
+        }
+    }
+    inlines.finish()
+}
+
+fn is_url(src: &str) -> bool {
+    ["http://", "https://", "//", "ftp://", "mailto:"]
+        .iter()
+        .any(|scheme| src.starts_with(scheme))
+}
+
+fn link(inner: &str, trail: &str) -> Option<Inline> {
+    let parts = split_top_level(inner, "|");
+    let target = parts[0].trim();
+    let lower = target.to_lowercase();
+    if HIDDEN_NAMESPACES.iter().any(|ns| lower.starts_with(ns)) {
+        return None;
+    }
+    let target = target.trim_start_matches(':');
+    let label = match inner.get(parts[0].len() + 1..) {
+        Some(label) if !label.trim().is_empty() => format!("{}{}", label, trail),
+        _ => format!("{}{}", target, trail),
+    };
+    Some(Inline::Link {
+        target: target.to_owned(),
+        label: trim(inline(&label)),
+    })
+}
+
+fn template(inner: &str) -> Template {
+    let mut parts = split_top_level(inner, "|").into_iter();
+    Template {
+        name: parts.next().unwrap_or_default().trim().to_owned(),
+        args: parts.map(|arg| arg.trim().to_owned()).collect(),
+    }
+}
+
+/// Handle an html-like tag at the start of `rest`, returning how many bytes
+/// were consumed.
+fn tag(caps: &regex::Captures, rest: &str, inlines: &mut Inlines) -> usize {
+    let closing = !caps[1].is_empty();
+    let name = caps[2].to_ascii_lowercase();
+    let attrs = &caps[3];
+    let self_closing = !caps[4].is_empty() || attrs.ends_with('/');
+    let open = caps[0].len();
+    if closing || (self_closing && name != "ref") {
+        if name == "br" {
+            inlines.push(Inline::LineBreak);
+        }
+        return open;
+    }
+    let close = format!("</{}>", name);
+    let content = |inlines: &mut Inlines, f: &dyn Fn(&str, &mut Inlines)| match find_ignore_case(
+        &rest[open..],
+        &close,
+    ) {
+        Some(end) => {
+            f(&rest[open..open + end], inlines);
+            open + end + close.len()
+        }
+        None => open,
+    };
+    match name.as_str() {
+        "br" => {
+            inlines.push(Inline::LineBreak);
+            open
+        }
+        "ref" => {
+            let name = REF_NAME
+                .captures(attrs)
+                .map(|caps| caps[1].trim().to_owned());
+            if self_closing {
+                inlines.push(Inline::Ref {
+                    name,
+                    content: Vec::new(),
+                });
+                open
+            } else {
+                content(inlines, &|text, inlines| {
+                    inlines.push(Inline::Ref {
+                        name: name.clone(),
+                        content: trim(inline(text)),
+                    })
+                })
+            }
+        }
+        _ if LITERAL_TAGS.contains(&name.as_str()) => {
+            content(inlines, &|text, inlines| inlines.push_str(text))
+        }
+        _ if HIDDEN_TAGS.contains(&name.as_str()) => content(inlines, &|_, _| {}),
+        // Any other tag is dropped, its content is parsed as usual
+        _ => open,
+    }
+}
+
+/// Replace html entities such as `&quot;` with the characters they stand for.
+pub fn decode_entities(src: &str) -> String {
+    let mut text = String::with_capacity(src.len());
+    let mut rest = src;
+    while let Some(i) = rest.find('&') {
+        text.push_str(&rest[..i]);
+        rest = &rest[i..];
+        match ENTITY.captures(rest) {
+            Some(caps) => {
+                match entity(&caps[1]) {
+                    Some(c) => text.push(c),
+                    None => text.push_str(&caps[0]),
+                }
+                rest = &rest[caps[0].len()..];
+            }
+            None => {
+                text.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    text.push_str(rest);
+    text
+}
+
+fn entity(name: &str) -> Option<char> {
+    if let Some(code) = name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+        return u32::from_str_radix(code, 16)
+            .ok()
+            .and_then(std::char::from_u32);
+    }
+    if let Some(code) = name.strip_prefix('#') {
+        return code.parse().ok().and_then(std::char::from_u32);
+    }
+    Some(match name {
+        "nbsp" => '\u{a0}',
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "ndash" => '–',
+        "mdash" => '—',
+        "minus" => '−',
+        "times" => '×',
+        "deg" => '°',
+        "hellip" => '…',
+        "thinsp" | "ensp" | "emsp" => ' ',
+        _ => return None,
+    })
+}
+
+/// Remove leading and trailing whitespace of a run of inlines.
+fn trim(mut content: Vec<Inline>) -> Vec<Inline> {
+    if let Some(Inline::Text(text)) = content.first_mut() {
+        *text = text.trim_start().to_owned();
+    }
+    if let Some(Inline::Text(text)) = content.last_mut() {
+        *text = text.trim_end().to_owned();
+    }
+    content.retain(|inline| !matches!(inline, Inline::Text(text) if text.is_empty()));
+    content
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Emphasis {
+    Bold,
+    Italic,
+}
+
+/// Builder for a run of inlines, keeping track of open bold/italic spans.
+#[derive(Default)]
+struct Inlines {
+    root: Vec<Inline>,
+    open: Vec<(Emphasis, Vec<Inline>)>,
+}
+
+impl Inlines {
+    fn current(&mut self) -> &mut Vec<Inline> {
+        match self.open.last_mut() {
+            Some((_, content)) => content,
+            None => &mut self.root,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.root.is_empty() && self.open.is_empty()
+    }
+
+    fn push(&mut self, inline: Inline) {
+        let current = self.current();
+        if let (Some(Inline::Text(last)), Inline::Text(text)) = (current.last_mut(), &inline) {
+            last.push_str(text);
+        } else {
+            current.push(inline);
+        }
+    }
+
+    fn push_str(&mut self, text: &str) {
+        if !text.is_empty() {
+            self.push(Inline::Text(text.to_owned()));
+        }
+    }
+
+    fn extend(&mut self, content: Vec<Inline>) {
+        for each in content {
+            self.push(each);
+        }
+    }
+
+    fn close(&mut self) -> Option<Emphasis> {
+        let (emphasis, content) = self.open.pop()?;
+        if !content.is_empty() {
+            self.push(match emphasis {
+                Emphasis::Bold => Inline::Bold(content),
+                Emphasis::Italic => Inline::Italic(content),
+            });
+        }
+        Some(emphasis)
+    }
+
+    /// Open `emphasis`, or close it along with everything opened after it.
+    /// Spans closed on the way are reopened so `'''a ''b''' c''` nests.
+    fn toggle(&mut self, emphasis: Emphasis) {
+        match self.open.iter().rposition(|(each, _)| *each == emphasis) {
+            Some(index) => {
+                let mut reopen = Vec::new();
+                while self.open.len() > index + 1 {
+                    reopen.extend(self.close());
+                }
+                self.close();
+                for each in reopen.into_iter().rev() {
+                    self.open.push((each, Vec::new()));
+                }
+            }
+            None => self.open.push((emphasis, Vec::new())),
+        }
+    }
+
+    fn finish(mut self) -> Vec<Inline> {
+        while self.close().is_some() {}
+        self.root
+    }
+
+    fn flush_into(&mut self, blocks: &mut Vec<Block>) {
+        let content = trim(std::mem::take(self).finish());
+        if !content.is_empty() {
+            blocks.push(Block::Paragraph(content));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(text: &str) -> Inline {
+        Inline::Text(text.to_owned())
+    }
+
+    #[test]
+    fn parse_heading_and_paragraph() {
+        let document = parse("'''Rust''' is\nan ''iron oxide''.\n\n== Chemistry ==\nIron.");
+        assert_eq!(
+            document.blocks,
+            vec![
+                Block::Paragraph(vec![
+                    Inline::Bold(vec![text("Rust")]),
+                    text(" is an "),
+                    Inline::Italic(vec![text("iron oxide")]),
+                    text("."),
+                ]),
+                Block::Heading {
+                    level: 2,
+                    content: vec![text("Chemistry")],
+                },
+                Block::Paragraph(vec![text("Iron.")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_nested_emphasis() {
+        assert_eq!(
+            inline("'''''both''' italic''"),
+            vec![Inline::Italic(vec![
+                Inline::Bold(vec![text("both")]),
+                text(" italic"),
+            ])]
+        );
+        assert_eq!(
+            inline("'''bold ''both''' italic''"),
+            vec![
+                Inline::Bold(vec![text("bold "), Inline::Italic(vec![text("both")])]),
+                Inline::Italic(vec![text(" italic")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_links() {
+        assert_eq!(
+            inline("[[Iron oxide|oxides]] of [[iron]]s, see [https://example.org Example] [[File:Rust.jpg|thumb|[[Rust]]]]"),
+            vec![
+                Inline::Link {
+                    target: "Iron oxide".to_owned(),
+                    label: vec![text("oxides")],
+                },
+                text(" of "),
+                Inline::Link {
+                    target: "iron".to_owned(),
+                    label: vec![text("irons")],
+                },
+                text(", see "),
+                Inline::ExternalLink {
+                    url: "https://example.org".to_owned(),
+                    label: vec![text("Example")],
+                },
+                text(" "),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_refs_templates_and_entities() {
+        assert_eq!(
+            inline("A&nbsp;B{{lang|fr|Rouille}}<ref name=\"a\">See [[Iron]]</ref><ref name=a />"),
+            vec![
+                text("A\u{a0}B"),
+                Inline::Template(Template {
+                    name: "lang".to_owned(),
+                    args: vec!["fr".to_owned(), "Rouille".to_owned()],
+                }),
+                Inline::Ref {
+                    name: Some("a".to_owned()),
+                    content: vec![
+                        text("See "),
+                        Inline::Link {
+                            target: "Iron".to_owned(),
+                            label: vec![text("Iron")],
+                        },
+                    ],
+                },
+                Inline::Ref {
+                    name: Some("a".to_owned()),
+                    content: vec![],
+                },
+            ]
+        );
+        assert_eq!(find_ignore_case("Café</REF>", "</ref>"), Some(5));
+        assert_eq!(find_ignore_case("Café", "</ref>"), None);
+    }
+
+    #[test]
+    fn parse_block_templates_and_lists() {
+        let document = parse(
+            "{{Infobox\n| name = Rust\n| image = [[File:a.jpg]]\n}}\n* one\n** two\n# three\n: indented",
+        );
+        assert_eq!(
+            document.blocks,
+            vec![
+                Block::Template(Template {
+                    name: "Infobox".to_owned(),
+                    args: vec![
+                        "name = Rust".to_owned(),
+                        "image = [[File:a.jpg]]".to_owned()
+                    ],
+                }),
+                Block::List(vec![
+                    ListItem {
+                        depth: 1,
+                        kind: ListKind::Bullet,
+                        content: vec![text("one")],
+                    },
+                    ListItem {
+                        depth: 2,
+                        kind: ListKind::Bullet,
+                        content: vec![text("two")],
+                    },
+                    ListItem {
+                        depth: 1,
+                        kind: ListKind::Numbered,
+                        content: vec![text("three")],
+                    },
+                    ListItem {
+                        depth: 1,
+                        kind: ListKind::Definition,
+                        content: vec![text("indented")],
+                    },
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_table() {
+        let document = parse(
+            "{| class=\"wikitable\"\n|+ Oxides\n! Name !! Formula\n|-\n| style=\"x\" | [[Rust|Red rust]] || Fe2O3\n|}",
+        );
+        let cell = |header, content| Cell { header, content };
+        assert_eq!(
+            document.blocks,
+            vec![Block::Table(Table {
+                caption: Some(vec![text("Oxides")]),
+                rows: vec![
+                    vec![
+                        cell(true, vec![text("Name")]),
+                        cell(true, vec![text("Formula")]),
+                    ],
+                    vec![
+                        cell(
+                            false,
+                            vec![Inline::Link {
+                                target: "Rust".to_owned(),
+                                label: vec![text("Red rust")],
+                            }]
+                        ),
+                        cell(false, vec![text("Fe2O3")]),
+                    ],
+                ],
+            })]
+        );
+    }
+
+    #[test]
+    fn parse_quote_and_comments() {
+        let document = parse("<!-- hidden -->__NOTOC__<blockquote>Quoted\n\ntext</blockquote>");
+        assert_eq!(
+            document.blocks,
+            vec![Block::Quote(vec![
+                Block::Paragraph(vec![text("Quoted")]),
+                Block::Paragraph(vec![text("text")]),
+            ])]
+        );
+    }
+}